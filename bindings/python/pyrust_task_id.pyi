@@ -0,0 +1,33 @@
+from typing import Dict, List, Optional, Tuple
+
+class TaskIdError(Exception): ...
+class NotInBranchError(TaskIdError): ...
+class WrongCapturingGroupError(TaskIdError): ...
+class MissingTaskIdError(TaskIdError): ...
+
+def extract_task_id(
+    branch: str,
+    patterns: List[str],
+    *,
+    normalize_case: Optional[str] = None,
+    normalize_key_separator: Optional[str] = None,
+    normalize_number_width: Optional[int] = None,
+    aliases: Optional[Dict[str, str]] = None,
+) -> str: ...
+def extract_task_ids(
+    branch: str,
+    patterns: List[str],
+    *,
+    normalize_case: Optional[str] = None,
+    normalize_key_separator: Optional[str] = None,
+    normalize_number_width: Optional[int] = None,
+    aliases: Optional[Dict[str, str]] = None,
+) -> List[str]: ...
+def parse_message(text: str, comment_string: str = "#") -> Tuple[str, str]: ...
+def render(
+    template: str,
+    subject: str,
+    body: str,
+    task_id: str,
+    groups: Optional[Dict[str, str]] = None,
+) -> str: ...
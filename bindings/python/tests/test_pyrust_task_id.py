@@ -0,0 +1,74 @@
+import pytest
+
+import pyrust_task_id
+
+PATTERNS = [r"feature/(?P<task_template>ABC-\d+)", r"(?P<task_template>gh-\d+)"]
+
+
+def test_extract_task_id():
+    assert pyrust_task_id.extract_task_id("feature/ABC-12-fix", PATTERNS) == "ABC-12"
+    assert pyrust_task_id.extract_task_id("gh-7-docs", PATTERNS) == "gh-7"
+
+
+def test_extract_task_ids():
+    task_ids = pyrust_task_id.extract_task_ids(
+        "ABC-1-ABC-2", [r"(?P<task_template>ABC-\d+)"]
+    )
+
+    assert task_ids == ["ABC-1", "ABC-2"]
+
+
+def test_extract_task_id_is_normalized_as_in_hook():
+    task_id = pyrust_task_id.extract_task_id(
+        "feature/abc_007-fix",
+        [r"feature/(?P<task_template>[a-z]+_\d+)"],
+        normalize_case="upper",
+        normalize_key_separator="-",
+        normalize_number_width=0,
+        aliases={"ABC": "WEB"},
+    )
+
+    assert task_id == "WEB-7"
+
+
+def test_parse_message():
+    text = "Subject\n# Comment\n\nBody\n"
+
+    assert pyrust_task_id.parse_message(text) == ("Subject", "Body")
+    assert pyrust_task_id.parse_message("Subject\n; Comment\n", ";") == ("Subject", "")
+
+
+def test_render():
+    message = pyrust_task_id.render(
+        "{type}: {subject}\n\n{body}\n\n{task_id}",
+        "Subject",
+        "Body",
+        "ABC-1",
+        {"type": "fix"},
+    )
+
+    assert message == "fix: Subject\n\nBody\n\nABC-1"
+
+
+def test_not_in_branch_error():
+    with pytest.raises(pyrust_task_id.NotInBranchError):
+        pyrust_task_id.extract_task_id("main", PATTERNS)
+
+    assert issubclass(pyrust_task_id.NotInBranchError, pyrust_task_id.TaskIdError)
+
+
+def test_wrong_capturing_group_error():
+    with pytest.raises(pyrust_task_id.WrongCapturingGroupError):
+        pyrust_task_id.extract_task_id("feature/ABC-1", [r"feature/(?P<task>ABC-\d+)"])
+
+    assert issubclass(
+        pyrust_task_id.WrongCapturingGroupError, pyrust_task_id.TaskIdError
+    )
+
+
+def test_incorrect_input_raises_value_error():
+    with pytest.raises(ValueError):
+        pyrust_task_id.extract_task_id("feature/ABC-1", ["(?P<task_template>"])
+
+    with pytest.raises(ValueError):
+        pyrust_task_id.render("{subject", "Subject", "", "ABC-1")
@@ -0,0 +1,1086 @@
+use crate::config::{
+    discover_config, read_config_file, Config, ConfigError, FileConfig,
+    OtherTaskId,
+};
+use crate::conventional::{NonConventional, Placement};
+use crate::git::{
+    copy_commit, create_branch, get_current_branch, get_git_dir,
+    get_hooks_dir, list_commits, parse_pre_push_refs, parse_ref_updates,
+    resolve_current_branch, resolve_revision, update_ref,
+};
+use crate::normalize::{Case, Normalization};
+use crate::skip::SkipRule;
+use crate::{
+    hooks, logger, read_file, CommitCheck, Error, Outcome, TaskIdInjector,
+};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{
+    ArgAction, ArgMatches, Args, Command, CommandFactory, FromArgMatches,
+    Parser, Subcommand, ValueEnum,
+};
+use serde_json::json;
+use similar::TextDiff;
+use std::collections::{HashMap, HashSet};
+use std::env::{current_dir, current_exe, var};
+use std::io::{read_to_string, stdin};
+use std::path::{absolute, Path, PathBuf};
+
+/// Provide task id retrieved from branch name into commit message
+#[derive(Parser)]
+#[command(
+    version,
+    subcommand_negates_reqs = true,
+    override_usage = "pyrust_task_id [OPTIONS] [TASK_REGEX COMMIT_MESSAGE_TEMPLATE] COMMIT_MESSAGE_FILE\n       pyrust_task_id [OPTIONS] <COMMAND>"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// `[TASK_REGEX COMMIT_MESSAGE_TEMPLATE] COMMIT_MESSAGE_FILE`
+    ///
+    /// Task regex and template may be omitted if they are set in config,
+    /// file is omitted with `--stdin`
+    #[arg(
+        required_unless_present = "stdin",
+        num_args = 1..=3,
+        value_name = "ARGS"
+    )]
+    args: Vec<String>,
+
+    /// Read commit message from stdin and print the result, implies
+    /// `--dry-run`
+    #[arg(long)]
+    stdin: bool,
+
+    /// Branch name to use instead of the current one
+    #[arg(long, global = true)]
+    branch: Option<String>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    #[command(flatten)]
+    options: ConfigArgs,
+}
+
+/// Options to preview the result without changing commit message file
+#[derive(Args)]
+struct OutputArgs {
+    /// Print the resulting commit message instead of writing it into the
+    /// file
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Print unified diff between the original and the resulting commit
+    /// message, implies `--dry-run`
+    #[arg(long, global = true)]
+    diff: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Check that task id is either in branch name or in commit message
+    /// without changing the message
+    Check { commit_message_file: String },
+
+    /// Pre-fill commit message opened in editor, use it on
+    /// `prepare-commit-msg` stage
+    PrepareCommitMsg {
+        commit_message_file: String,
+
+        /// Source of the commit message: `message`, `template`, `merge`,
+        /// `squash` or `commit`
+        #[arg(env = "PRE_COMMIT_COMMIT_MSG_SOURCE")]
+        commit_source: Option<String>,
+
+        /// SHA of the commit if the source is `commit`
+        #[arg(env = "PRE_COMMIT_COMMIT_OBJECT_NAME")]
+        commit_sha: Option<String>,
+    },
+
+    /// Check that every commit in the range contains task id in its
+    /// message, use it on `pre-push` stage or in CI
+    CheckRange {
+        /// Commits to check, e.g. `origin/main..HEAD`. By default range is
+        /// taken from `PRE_COMMIT_FROM_REF` and `PRE_COMMIT_TO_REF` or from
+        /// refs `pre-push` hook receives on stdin
+        range: Option<String>,
+
+        /// Output format, `json` includes status of every commit
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+
+    /// Insert task id into messages of existing commits of the current
+    /// branch and print `<old sha> <new sha>` for every rewritten commit
+    Rewrite {
+        /// Commits to rewrite, e.g. `origin/main..HEAD`, range must end at
+        /// `HEAD`
+        range: String,
+
+        /// Rewrite pushed and merge commits too
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Install `commit-msg` git hook running this binary without pre-commit,
+    /// existing hook keeps running before it
+    Install {
+        /// Install `prepare-commit-msg` hook as well, so task id is already
+        /// in the editor
+        #[arg(long)]
+        prepare_commit_msg: bool,
+    },
+
+    /// Remove hooks written by `install` and restore the previous ones
+    Uninstall,
+
+    /// Check that branch name matches task regex unless the branch is exempt
+    LintBranch {
+        /// Check branches from refs the hook receives on stdin instead of
+        /// the current branch
+        #[arg(long, value_enum)]
+        hook: Option<RefHook>,
+
+        /// Transaction state passed to `reference-transaction` hook, only
+        /// `prepared` transactions are checked
+        state: Option<String>,
+    },
+
+    /// Create branch for the task from `branch-template` and check it out,
+    /// `--dry-run` only prints its name
+    NewBranch {
+        /// Id of the task, e.g. `ABC-123`
+        task_id: String,
+
+        /// Title of the task, it is turned into slug like `fix-login`
+        title: String,
+    },
+
+    /// Print task id retrieved from branch name, exit code is 3 if branch
+    /// doesn't match task regex and 4 if regex has no `task_template` group
+    Extract {
+        /// Output format, `json` includes matched rule, named groups and
+        /// where branch name was taken from
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+}
+
+/// Hook passing updated refs on stdin
+#[derive(ValueEnum, Clone, Copy, PartialEq)]
+enum RefHook {
+    /// Pushed branches are checked by their remote name
+    PrePush,
+    /// Only created branches are checked, so existing ones can be committed
+    /// to
+    ReferenceTransaction,
+}
+
+/// Output format of commands printing data for scripts
+#[derive(ValueEnum, Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+/// Options that override values from config file
+#[derive(Args)]
+struct ConfigArgs {
+    /// Regex with `task_template` named capturing group, may be repeated
+    /// to try several regexes in the given order
+    #[arg(long, global = true)]
+    task_regex: Vec<String>,
+
+    /// Template of the commit message
+    #[arg(long, global = true)]
+    template: Option<String>,
+
+    /// Insert every task id found in branch name, not only the first one
+    #[arg(long, global = true)]
+    all_task_ids: bool,
+
+    /// String used to join several task ids
+    #[arg(long, global = true)]
+    task_id_separator: Option<String>,
+
+    /// Fail if task id is neither in branch name nor in commit message
+    #[arg(long, global = true)]
+    require: bool,
+
+    /// Regex of task id used to find it in commit message, by default
+    /// pattern of `task_template` group is used
+    #[arg(long, global = true)]
+    task_id_regex: Vec<String>,
+
+    /// Branch that doesn't require task id, may be repeated
+    #[arg(long, global = true)]
+    exempt_branch: Vec<String>,
+
+    /// Insert task id as trailer with the given key, e.g. `Refs`, instead
+    /// of using template
+    #[arg(long, global = true)]
+    trailer: Option<String>,
+
+    /// Insert task id into Conventional Commits subject instead of using
+    /// template
+    #[arg(long, global = true)]
+    conventional: Option<Placement>,
+
+    /// What to do with subject which doesn't follow Conventional Commits
+    #[arg(long, global = true)]
+    non_conventional: Option<NonConventional>,
+
+    /// Find task id in commit message ignoring case
+    #[arg(long, global = true)]
+    ignore_case: bool,
+
+    /// What to do if commit message contains task id other than the one
+    /// from branch name
+    #[arg(long, global = true)]
+    other_task_id: Option<OtherTaskId>,
+
+    /// Kind of commits left untouched, may be repeated, replaces default
+    /// rules
+    #[arg(long, global = true)]
+    skip: Vec<SkipRule>,
+
+    /// Template of the branch name created by `new-branch`, e.g.
+    /// `feature/{task_id}-{slug}`
+    #[arg(long, global = true)]
+    branch_template: Option<String>,
+
+    /// Convert task id to the given case
+    #[arg(long, global = true)]
+    normalize_case: Option<Case>,
+
+    /// Put the given string between project key and number of task id
+    #[arg(long, global = true)]
+    normalize_key_separator: Option<String>,
+
+    /// Pad number of task id with zeros to the given width, `0` strips
+    /// leading zeros
+    #[arg(long, global = true)]
+    normalize_number_width: Option<usize>,
+
+    /// Project key alias as `ALIAS=KEY`, may be repeated, task ids with the
+    /// alias get the canonical key
+    #[arg(long, global = true, value_parser = parse_alias)]
+    alias: Vec<(String, String)>,
+
+    /// Take branch name of detached HEAD from `git name-rev`, any local
+    /// branch containing the commit may be found
+    #[arg(long, global = true)]
+    detached_name_rev: bool,
+
+    /// Pre-fill empty commit message on `prepare-commit-msg` stage, the
+    /// commit is then aborted only if the whole message is removed
+    #[arg(long, global = true)]
+    prefill_empty: bool,
+
+    /// Path to config file, by default `.pyrust-task-id.toml` or
+    /// `pyproject.toml` is searched walking up from current directory
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Show what is going on, pass twice for debug output
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+}
+
+/// Parse project key alias passed as `ALIAS=KEY`
+///
+/// * `value` - value of `--alias` option
+fn parse_alias(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((alias, key)) if !alias.is_empty() && !key.is_empty() => {
+            Ok((alias.to_string(), key.to_string()))
+        }
+        _ => Err(format!("expected `ALIAS=KEY`, got `{value}`")),
+    }
+}
+
+/// Build config from config file and command line arguments
+///
+/// Command line arguments take precedence over values from config file.
+///
+/// * `options` - options passed via command line
+/// * `positional_regex` - task regex passed as positional argument
+/// * `positional_template` - template passed as positional argument
+fn load_config(
+    options: &ConfigArgs,
+    positional_regex: Option<&String>,
+    positional_template: Option<&String>,
+) -> Result<Config, Error> {
+    let file_config = match &options.config {
+        Some(path) => Some(
+            read_config_file(path)?
+                .ok_or_else(|| ConfigError::MissingTable(path.clone()))?,
+        ),
+        None => {
+            let directory = current_dir()
+                .map_err(|err| ConfigError::Io(PathBuf::from("."), err))?;
+            discover_config(&directory)?.map(|(_, config)| config)
+        }
+    };
+
+    let non_empty = |values: &[String]| match values {
+        [] => None,
+        values => Some(values.to_vec()),
+    };
+    let cli_config = FileConfig {
+        task_regex: non_empty(&options.task_regex)
+            .or(positional_regex.map(|task_regex| vec![task_regex.clone()])),
+        template: options.template.clone().or(positional_template.cloned()),
+        all_task_ids: options.all_task_ids.then_some(true),
+        task_id_separator: options.task_id_separator.clone(),
+        require: options.require.then_some(true),
+        task_id_regex: non_empty(&options.task_id_regex),
+        exempt_branches: non_empty(&options.exempt_branch),
+        trailer: options.trailer.clone(),
+        conventional: options.conventional,
+        non_conventional: options.non_conventional,
+        ignore_case: options.ignore_case.then_some(true),
+        other_task_id: options.other_task_id,
+        skip: (!options.skip.is_empty()).then(|| options.skip.clone()),
+        branch_template: options.branch_template.clone(),
+        detached_name_rev: options.detached_name_rev.then_some(true),
+        prefill_empty: options.prefill_empty.then_some(true),
+        normalize: (options.normalize_case.is_some()
+            || options.normalize_key_separator.is_some()
+            || options.normalize_number_width.is_some())
+        .then(|| Normalization {
+            case: options.normalize_case,
+            key_separator: options.normalize_key_separator.clone(),
+            number_width: options.normalize_number_width,
+        }),
+        aliases: (!options.alias.is_empty())
+            .then(|| options.alias.iter().cloned().collect()),
+    };
+
+    Ok(Config::try_from(
+        file_config.unwrap_or_default().merge(cli_config),
+    )?)
+}
+
+/// Build injector for commit hooks, skipping commits while merge or
+/// cherry-pick is in progress in the current repository
+///
+/// * `options` - options passed via command line
+/// * `positional_regex` - task regex passed as positional argument
+/// * `positional_template` - template passed as positional argument
+fn load_hook_injector(
+    options: &ConfigArgs,
+    positional_regex: Option<&String>,
+    positional_template: Option<&String>,
+) -> Result<TaskIdInjector, Error> {
+    let mut injector = TaskIdInjector::from(load_config(
+        options,
+        positional_regex,
+        positional_template,
+    )?);
+    injector.git_dir =
+        get_git_dir().inspect_err(|err| log::debug!("{err}")).ok();
+    Ok(injector)
+}
+
+/// Return branch name passed via command line or the current one
+///
+/// * `branch` - value of `--branch` option
+/// * `config` - config telling how to resolve detached HEAD
+fn get_branch_name(
+    branch: &Option<String>,
+    config: &Config,
+) -> Result<String, Error> {
+    match branch {
+        Some(branch) => Ok(branch.clone()),
+        None => get_current_branch(config.detached_name_rev),
+    }
+}
+
+/// Read commit message from stdin
+fn read_stdin() -> Result<String, Error> {
+    read_to_string(stdin())
+        .map_err(|err| Error::Io(PathBuf::from("<stdin>"), err))
+}
+
+/// Print the resulting commit message or its diff with the original one
+///
+/// * `output` - output options
+/// * `name` - name of commit message file shown in diff header
+/// * `commit_message` - the original commit message
+/// * `outcome` - result of providing task id into commit message
+fn print_result(
+    output: &OutputArgs,
+    name: &str,
+    commit_message: &str,
+    outcome: &Outcome,
+) {
+    let result = match outcome {
+        Outcome::Updated(updated_commit_message) => updated_commit_message,
+        _ => commit_message,
+    };
+
+    if output.diff {
+        print!(
+            "{}",
+            TextDiff::from_lines(commit_message, result)
+                .unified_diff()
+                .header(name, name)
+        );
+    } else if result.ends_with('\n') {
+        print!("{result}");
+    } else {
+        println!("{result}");
+    }
+}
+
+/// Return revisions of commits to check
+///
+/// Explicit range wins, then range passed by pre-commit and then refs
+/// `pre-push` hook receives on stdin. Refs which don't exist on remote yet
+/// are checked from the first commit missing on every remote.
+///
+/// * `range` - range passed via command line
+fn get_revisions(range: &Option<String>) -> Result<Vec<Vec<String>>, Error> {
+    let revisions = |from: &str, to: &str| {
+        if from.chars().all(|char| char == '0') {
+            vec![
+                to.to_string(),
+                String::from("--not"),
+                String::from("--remotes"),
+            ]
+        } else {
+            vec![format!("{from}..{to}")]
+        }
+    };
+
+    if let Some(range) = range {
+        return Ok(vec![vec![range.clone()]]);
+    }
+    if let (Ok(from), Ok(to)) =
+        (var("PRE_COMMIT_FROM_REF"), var("PRE_COMMIT_TO_REF"))
+    {
+        return Ok(vec![revisions(&from, &to)]);
+    }
+
+    Ok(parse_pre_push_refs(&read_stdin()?)
+        .iter()
+        .filter(|pushed_ref| !pushed_ref.is_deletion())
+        .map(|pushed_ref| {
+            revisions(&pushed_ref.remote_sha, &pushed_ref.local_sha)
+        })
+        .collect())
+}
+
+/// Check that every commit in the range contains task id
+///
+/// * `injector` - injector with task id regexes and skip rules
+/// * `range` - range passed via command line
+/// * `format` - output format
+fn check_range(
+    injector: &TaskIdInjector,
+    range: &Option<String>,
+    format: Format,
+) -> Result<(), Error> {
+    let mut checked = Vec::new();
+    let mut seen = HashSet::new();
+    for revisions in get_revisions(range)? {
+        let revisions: Vec<&str> =
+            revisions.iter().map(String::as_str).collect();
+        for commit in list_commits(&revisions)? {
+            if !seen.insert(commit.sha.clone()) {
+                continue;
+            }
+            let check = injector
+                .check_commit(&commit.message, commit.parents.len())?;
+            let subject = commit
+                .message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            checked.push((commit.sha, subject, check));
+        }
+    }
+
+    let missing = checked
+        .iter()
+        .filter(|(_, _, check)| *check == CommitCheck::MissingTaskId)
+        .count();
+
+    match format {
+        Format::Text => {
+            for (sha, subject, check) in &checked {
+                match check {
+                    CommitCheck::HasTaskId => {}
+                    CommitCheck::Skipped(reason) => {
+                        log::info!("{sha:.10} {reason}")
+                    }
+                    CommitCheck::MissingTaskId => {
+                        println!("{sha:.10} {subject}")
+                    }
+                }
+            }
+        }
+        Format::Json => {
+            let commits: Vec<_> = checked
+                .iter()
+                .map(|(sha, subject, check)| {
+                    let (status, reason) = match check {
+                        CommitCheck::HasTaskId => ("ok", None),
+                        CommitCheck::Skipped(reason) => {
+                            ("skipped", Some(reason))
+                        }
+                        CommitCheck::MissingTaskId => ("missing", None),
+                    };
+                    json!({
+                        "sha": sha,
+                        "subject": subject,
+                        "status": status,
+                        "reason": reason,
+                    })
+                })
+                .collect();
+            println!(
+                "{}",
+                json!({
+                    "checked": checked.len(),
+                    "missing": missing,
+                    "commits": commits,
+                })
+            );
+        }
+    }
+
+    if missing > 0 {
+        return Err(Error::MissingTaskId(format!(
+            "{missing} of {} commits don't contain task id.",
+            checked.len()
+        )));
+    }
+    log::info!("All {} commits contain task id.", checked.len());
+
+    Ok(())
+}
+
+/// Rewrite messages of commits in the range which end at `HEAD`
+///
+/// Commits are copied with new messages and parents, then the current branch
+/// is moved to the copy of `HEAD`. Tree of every commit stays the same, so
+/// the working tree is untouched.
+///
+/// * `injector` - injector used to update every message
+/// * `branch_name` - branch to retrieve task id from
+/// * `range` - commits to rewrite
+/// * `force` - rewrite pushed and merge commits too
+/// * `dry_run` - print the mapping without moving the branch
+fn rewrite(
+    injector: &TaskIdInjector,
+    branch_name: &str,
+    range: &str,
+    force: bool,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let head = resolve_revision("HEAD")?;
+    let commits = list_commits(&[range])?;
+    if commits.last().is_none_or(|commit| commit.sha != head) {
+        return Err(Error::UnsafeRewrite(format!(
+            "Range `{range}` must end at `HEAD`, so the rewritten commits are reachable from the current branch."
+        )));
+    }
+
+    if !force {
+        let unpushed: HashSet<String> =
+            list_commits(&[range, "--not", "--remotes"])?
+                .into_iter()
+                .map(|commit| commit.sha)
+                .collect();
+        for commit in &commits {
+            let reason = if commit.parents.len() > 1 {
+                "is a merge commit"
+            } else if !unpushed.contains(&commit.sha) {
+                "is already pushed"
+            } else {
+                continue;
+            };
+            return Err(Error::UnsafeRewrite(format!(
+                "Commit `{:.10}` {reason}, use `--force` to rewrite it anyway.",
+                commit.sha
+            )));
+        }
+    }
+
+    let mut rewritten: HashMap<String, String> = HashMap::new();
+    for commit in &commits {
+        let parents: Vec<String> = commit
+            .parents
+            .iter()
+            .map(|parent| rewritten.get(parent).unwrap_or(parent).clone())
+            .collect();
+        let message = match injector.update_commit(
+            &commit.message,
+            commit.parents.len(),
+            branch_name,
+        )? {
+            Outcome::Updated(message) => format!("{}\n", message.trim_end()),
+            Outcome::NoTaskId(err) => return Err(err.into()),
+            _ => commit.message.clone(),
+        };
+
+        if parents != commit.parents || message != commit.message {
+            let new_sha = copy_commit(commit, &parents, &message)?;
+            println!("{} {new_sha}", commit.sha);
+            rewritten.insert(commit.sha.clone(), new_sha);
+        }
+    }
+
+    match rewritten.get(&head) {
+        Some(new_head) if !dry_run => {
+            update_ref("HEAD", new_head, &head, "pyrust_task_id: rewrite")
+        }
+        Some(_) => Ok(()),
+        None => {
+            log::info!("Every commit already contains task id.");
+            Ok(())
+        }
+    }
+}
+
+/// Return names of branches to lint
+///
+/// * `branch` - branch passed via command line
+/// * `config` - config telling how to resolve detached HEAD
+/// * `hook` - hook passing updated refs on stdin
+/// * `state` - transaction state passed to `reference-transaction` hook
+fn get_linted_branches(
+    branch: &Option<String>,
+    config: &Config,
+    hook: Option<RefHook>,
+    state: Option<&str>,
+) -> Result<Vec<String>, Error> {
+    let branch_names = |references: Vec<String>| {
+        references
+            .iter()
+            .filter_map(|reference| reference.strip_prefix("refs/heads/"))
+            .map(String::from)
+            .collect()
+    };
+
+    match hook {
+        None => Ok(vec![get_branch_name(branch, config)?]),
+        Some(RefHook::PrePush) => Ok(branch_names(
+            parse_pre_push_refs(&read_stdin()?)
+                .into_iter()
+                .filter(|pushed_ref| !pushed_ref.is_deletion())
+                .map(|pushed_ref| pushed_ref.remote_ref)
+                .collect(),
+        )),
+        Some(RefHook::ReferenceTransaction) if state == Some("prepared") => {
+            Ok(branch_names(
+                parse_ref_updates(&read_stdin()?)
+                    .into_iter()
+                    .filter(|update| update.is_creation())
+                    .map(|update| update.reference)
+                    .collect(),
+            ))
+        }
+        Some(RefHook::ReferenceTransaction) => Ok(Vec::new()),
+    }
+}
+
+/// Install hooks running the current executable
+///
+/// * `prepare_commit_msg` - install `prepare-commit-msg` hook as well
+fn install_hooks(
+    prepare_commit_msg: bool,
+    options: &[String],
+) -> Result<(), Error> {
+    let hooks_dir = get_hooks_dir()?;
+    let executable = current_exe().map_err(|err| {
+        Error::Io(PathBuf::from("<current executable>"), err)
+    })?;
+    let executable = executable.to_string_lossy();
+    let options = options.iter().map(String::as_str);
+
+    let command: Vec<&str> = [&executable as &str]
+        .into_iter()
+        .chain(options.clone())
+        .collect();
+    hooks::install(&hooks_dir, "commit-msg", &command)?;
+    if prepare_commit_msg {
+        let command: Vec<&str> = [&executable as &str, "prepare-commit-msg"]
+            .into_iter()
+            .chain(options)
+            .collect();
+        hooks::install(&hooks_dir, "prepare-commit-msg", &command)?;
+    }
+
+    Ok(())
+}
+
+/// Return config options given in command line, so installed hooks run
+/// with them
+///
+/// Config path is made absolute as hooks run from the worktree root.
+///
+/// * `matches` - parsed command line
+fn hook_options(matches: &ArgMatches) -> Result<Vec<String>, Error> {
+    let mut options = Vec::new();
+    for arg in ConfigArgs::augment_args(Command::new("hook")).get_arguments() {
+        let id = arg.get_id().as_str();
+        if id == "verbose"
+            || matches.value_source(id) != Some(ValueSource::CommandLine)
+        {
+            continue;
+        }
+
+        let flag = format!("--{}", arg.get_long().unwrap_or(id));
+        if !arg.get_action().takes_values() {
+            options.push(flag);
+            continue;
+        }
+        for value in matches.get_raw(id).into_iter().flatten() {
+            let value = match id {
+                "config" => absolute(value)
+                    .map_err(|err| Error::Io(PathBuf::from(value), err))?
+                    .into_os_string(),
+                _ => value.to_owned(),
+            };
+            options.push(flag.clone());
+            options.push(value.to_string_lossy().into_owned());
+        }
+    }
+
+    Ok(options)
+}
+
+/// Remove hooks written by [`install_hooks`]
+fn uninstall_hooks() -> Result<(), Error> {
+    let hooks_dir = get_hooks_dir()?;
+    let mut removed = false;
+    for hook in ["commit-msg", "prepare-commit-msg"] {
+        removed |= hooks::uninstall(&hooks_dir, hook)?;
+    }
+    if !removed {
+        log::info!("No hooks are installed in `{}`.", hooks_dir.display());
+    }
+
+    Ok(())
+}
+
+/// Prase args and run
+pub fn parse_args_and_run() -> Result<(), Error> {
+    let matches = Cli::command().get_matches();
+    let args =
+        Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    logger::init(args.options.verbose);
+
+    if args.command.is_some() && !args.args.is_empty() {
+        Cli::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "Positional arguments can't be combined with a command.",
+            )
+            .exit();
+    }
+
+    let outcome = match &args.command {
+        Some(Commands::NewBranch { task_id, title }) => {
+            let injector =
+                TaskIdInjector::from(load_config(&args.options, None, None)?);
+            let branch_name = injector.branch_name(task_id, title)?;
+            if args.output.dry_run {
+                println!("{branch_name}");
+            } else {
+                create_branch(&branch_name)?;
+                log::info!("Switched to a new branch `{branch_name}`.");
+            }
+            return Ok(());
+        }
+        Some(Commands::LintBranch { hook, state }) => {
+            let injector =
+                TaskIdInjector::from(load_config(&args.options, None, None)?);
+            for branch_name in get_linted_branches(
+                &args.branch,
+                injector.config(),
+                *hook,
+                state.as_deref(),
+            )? {
+                injector.check_branch(&branch_name)?;
+                log::info!("Branch `{branch_name}` is named correctly.");
+            }
+            return Ok(());
+        }
+        Some(Commands::Install { prepare_commit_msg }) => {
+            // Fail now rather than on the first commit
+            load_config(&args.options, None, None)?;
+            return install_hooks(
+                *prepare_commit_msg,
+                &hook_options(&matches)?,
+            );
+        }
+        Some(Commands::Uninstall) => return uninstall_hooks(),
+        Some(Commands::Rewrite { range, force }) => {
+            let injector =
+                TaskIdInjector::from(load_config(&args.options, None, None)?);
+            return rewrite(
+                &injector,
+                &get_branch_name(&args.branch, injector.config())?,
+                range,
+                *force,
+                args.output.dry_run,
+            );
+        }
+        Some(Commands::CheckRange { range, format }) => {
+            let injector =
+                TaskIdInjector::from(load_config(&args.options, None, None)?);
+            return check_range(&injector, range, *format);
+        }
+        Some(Commands::Extract { format }) => {
+            let injector =
+                TaskIdInjector::from(load_config(&args.options, None, None)?);
+            let (branch_name, branch_source) = match &args.branch {
+                Some(branch) => (branch.clone(), None),
+                None => {
+                    let (branch, source) = resolve_current_branch(
+                        injector.config().detached_name_rev,
+                    )?;
+                    (branch, Some(source))
+                }
+            };
+
+            let task_match = injector.task_match(&branch_name)?;
+            match format {
+                Format::Text => println!("{}", task_match.task_ids.join("\n")),
+                Format::Json => println!(
+                    "{}",
+                    json!({
+                        "branch": branch_name,
+                        "branch_source": branch_source
+                            .map(|source| source.to_string()),
+                        "task_id": task_match.task_ids[0],
+                        "task_ids": task_match.task_ids,
+                        "rule": task_match.rule,
+                        "regex": injector.config().task_regexes[task_match.rule]
+                            .as_str(),
+                        "groups": task_match.groups,
+                    })
+                ),
+            }
+            return Ok(());
+        }
+        Some(Commands::Check {
+            commit_message_file,
+        }) => {
+            let injector = load_hook_injector(&args.options, None, None)?;
+            let branch_name =
+                get_branch_name(&args.branch, injector.config())?;
+            return injector.check_file(commit_message_file, &branch_name);
+        }
+        Some(Commands::PrepareCommitMsg {
+            commit_message_file,
+            commit_source,
+            commit_sha,
+        }) => {
+            log::debug!(
+                "Commit message source is {commit_source:?}, commit is {commit_sha:?}."
+            );
+            let injector = load_hook_injector(&args.options, None, None)?;
+            let branch_name =
+                get_branch_name(&args.branch, injector.config())?;
+            if args.output.dry_run || args.output.diff {
+                let commit_message =
+                    read_file(Path::new(commit_message_file))?;
+                let outcome = injector.prepare_message(
+                    &commit_message,
+                    &branch_name,
+                    commit_source.as_deref(),
+                )?;
+                print_result(
+                    &args.output,
+                    commit_message_file,
+                    &commit_message,
+                    &outcome,
+                );
+                outcome
+            } else {
+                injector.prepare_file(
+                    commit_message_file,
+                    &branch_name,
+                    commit_source.as_deref(),
+                )?
+            }
+        }
+        None => {
+            let (positional_regex, positional_template, commit_message_file) =
+                match (args.args.as_slice(), args.stdin) {
+                    ([task_regex, template, file], false) => {
+                        (Some(task_regex), Some(template), Some(file))
+                    }
+                    ([file], false) => (None, None, Some(file)),
+                    ([task_regex, template], true) => {
+                        (Some(task_regex), Some(template), None)
+                    }
+                    ([], true) => (None, None, None),
+                    (_, false) => Cli::command()
+                        .error(
+                            ErrorKind::WrongNumberOfValues,
+                            "Expected either `COMMIT_MESSAGE_FILE` or `TASK_REGEX COMMIT_MESSAGE_TEMPLATE COMMIT_MESSAGE_FILE`.",
+                        )
+                        .exit(),
+                    (_, true) => Cli::command()
+                        .error(
+                            ErrorKind::WrongNumberOfValues,
+                            "Expected either no arguments or `TASK_REGEX COMMIT_MESSAGE_TEMPLATE` with `--stdin`.",
+                        )
+                        .exit(),
+                };
+
+            let injector = load_hook_injector(
+                &args.options,
+                positional_regex,
+                positional_template,
+            )?;
+            let branch_name =
+                get_branch_name(&args.branch, injector.config())?;
+            match commit_message_file {
+                Some(file) if !args.output.dry_run && !args.output.diff => {
+                    injector.update_file(file, &branch_name)?
+                }
+                file => {
+                    let commit_message = match file {
+                        Some(file) => read_file(Path::new(file))?,
+                        None => read_stdin()?,
+                    };
+                    let outcome = injector
+                        .commit_message(&commit_message, &branch_name)?;
+                    print_result(
+                        &args.output,
+                        file.map_or("COMMIT_EDITMSG", String::as_str),
+                        &commit_message,
+                        &outcome,
+                    );
+                    outcome
+                }
+            }
+        }
+    };
+
+    match outcome {
+        Outcome::Updated(_) => log::info!("Commit message is updated."),
+        Outcome::AlreadyContainsTaskId => {
+            log::info!("Commit message already contains task id.")
+        }
+        Outcome::NoTaskId(err) => log::info!("{err}"),
+        Outcome::Skipped(reason) => log::info!("{reason}"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from([&["pyrust_task_id"], args].concat()).unwrap()
+    }
+
+    #[test]
+    fn test_cli_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_options_before_command() {
+        let cli = parse(&["-v", "extract"]);
+        assert!(matches!(cli.command, Some(Commands::Extract { .. })));
+        assert_eq!(cli.options.verbose, 1);
+
+        let cli = parse(&["--task-regex", "X", "check-range", "a..b"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::CheckRange { range: Some(_), .. })
+        ));
+        assert_eq!(cli.options.task_regex, vec![String::from("X")]);
+
+        // The way pre-commit passes hook arguments and the file name
+        let cli = parse(&["--require", "check", ".git/COMMIT_EDITMSG"]);
+        assert!(matches!(cli.command, Some(Commands::Check { .. })));
+        assert!(cli.options.require);
+    }
+
+    #[test]
+    fn test_positional_arguments_without_command() {
+        let cli = parse(&["-v", "regex", "template", ".git/COMMIT_EDITMSG"]);
+        assert!(cli.command.is_none());
+        assert_eq!(cli.args.len(), 3);
+
+        let cli = parse(&[".git/COMMIT_EDITMSG", "--dry-run"]);
+        assert!(cli.command.is_none());
+        assert!(cli.output.dry_run);
+
+        assert!(parse(&["--stdin"]).args.is_empty());
+        assert!(Cli::try_parse_from(["pyrust_task_id"]).is_err());
+    }
+
+    #[test]
+    fn test_hook_options_are_taken_from_command_line() {
+        let matches = Cli::command()
+            .try_get_matches_from([
+                "pyrust_task_id",
+                "-v",
+                "--task-regex",
+                "a",
+                "install",
+                "--require",
+                "--template",
+                "{task_id}",
+                "--prepare-commit-msg",
+            ])
+            .unwrap();
+
+        assert_eq!(
+            hook_options(&matches).unwrap(),
+            ["--task-regex", "a", "--template", "{task_id}", "--require",]
+        );
+
+        let matches = Cli::command()
+            .try_get_matches_from(["pyrust_task_id", "install"])
+            .unwrap();
+        assert!(hook_options(&matches).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_explicit_pyproject_must_contain_table() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("pyproject.toml");
+        std::fs::write(&path, "[project]\nname = 'test'\n").unwrap();
+
+        let cli = parse(&["--config", path.to_str().unwrap(), "extract"]);
+
+        assert!(matches!(
+            load_config(&cli.options, None, None),
+            Err(Error::Config(ConfigError::MissingTable(..)))
+        ));
+    }
+
+    #[test]
+    fn test_hook_config_path_is_absolute() {
+        let matches = Cli::command()
+            .try_get_matches_from([
+                "pyrust_task_id",
+                "install",
+                "--config",
+                "config.toml",
+            ])
+            .unwrap();
+
+        let options = hook_options(&matches).unwrap();
+        assert_eq!(options[0], "--config");
+        assert!(Path::new(&options[1]).is_absolute());
+        assert!(options[1].ends_with("config.toml"));
+    }
+}
@@ -0,0 +1,907 @@
+use crate::conventional::{NonConventional, Placement};
+use crate::normalize::Normalization;
+use crate::skip::{SkipRule, DEFAULT_SKIP_RULES};
+use crate::template::{Template, TemplateError};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::read_to_string;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the standalone configuration file
+pub const CONFIG_FILE_NAME: &str = ".pyrust-task-id.toml";
+
+/// Name of the python project file that may contain configuration table
+pub const PYPROJECT_FILE_NAME: &str = "pyproject.toml";
+
+/// Name of the table inside `[tool]` section of `pyproject.toml`
+const PYPROJECT_TABLE_NAME: &str = "pyrust-task-id";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    MissingOption(&'static str),
+    InvalidRegex(String, regex::Error),
+    InvalidTemplate(TemplateError),
+    InvalidTrailer(String),
+    ConflictingOptions(&'static str, &'static str),
+    AmbiguousTaskIdRegex(String),
+    MissingTable(PathBuf),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => {
+                write!(f, "Unable to read `{}`: {err}", path.display())
+            }
+            ConfigError::Parse(path, err) => {
+                write!(f, "Unable to parse `{}`: {err}", path.display())
+            }
+            ConfigError::MissingOption(option) => write!(
+                f,
+                "Option `{option}` must be provided either in command line or in config file."
+            ),
+            ConfigError::InvalidRegex(regex, err) => {
+                write!(f, "Make sure task regex `{regex}` is correct: {err}")
+            }
+            ConfigError::InvalidTemplate(err) => write!(f, "{err}"),
+            ConfigError::InvalidTrailer(key) => write!(
+                f,
+                "Trailer key `{key}` must contain only letters, digits and `-`."
+            ),
+            ConfigError::ConflictingOptions(first, second) => write!(
+                f,
+                "Options `{first}` and `{second}` can't be used together."
+            ),
+            ConfigError::AmbiguousTaskIdRegex(regex) => write!(
+                f,
+                "Task id pattern `{regex}` has no literal characters and would match unrelated text in commit message, set `task-id-regex` explicitly."
+            ),
+            ConfigError::MissingTable(path) => write!(
+                f,
+                "`{}` doesn't contain `[tool.{PYPROJECT_TABLE_NAME}]` table.",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(_, err) => Some(err),
+            ConfigError::Parse(_, err) => Some(err),
+            ConfigError::InvalidRegex(_, err) => Some(err),
+            ConfigError::InvalidTemplate(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// What to do if commit message contains task id other than the one from
+/// branch name
+#[derive(
+    Deserialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum OtherTaskId {
+    /// Insert task id from branch name anyway
+    #[default]
+    Add,
+    /// Leave commit message untouched
+    Skip,
+    /// Fail with error
+    Fail,
+}
+
+/// Raw configuration as it is written in config file or passed via CLI
+///
+/// All options are optional here, so values from different sources can be
+/// merged before validation.
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FileConfig {
+    /// Single regex or list of regexes tried in the given order
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub task_regex: Option<Vec<String>>,
+    pub template: Option<String>,
+    /// Insert every task id found in branch name instead of the first one
+    pub all_task_ids: Option<bool>,
+    /// String used to join several task ids, `\n` escapes are allowed
+    pub task_id_separator: Option<String>,
+    /// Fail if task id is neither in branch name nor in commit message
+    pub require: Option<bool>,
+    /// Regexes of task id itself used to find task id in commit message
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub task_id_regex: Option<Vec<String>>,
+    /// Branches that don't require task id, `*` wildcards are allowed
+    pub exempt_branches: Option<Vec<String>>,
+    /// Key of the trailer task id is inserted as instead of using template
+    pub trailer: Option<String>,
+    /// Where task id is inserted into Conventional Commits subject
+    pub conventional: Option<Placement>,
+    /// What to do with subject which doesn't follow Conventional Commits
+    pub non_conventional: Option<NonConventional>,
+    /// Find task id in commit message ignoring case
+    pub ignore_case: Option<bool>,
+    /// What to do if commit message contains another task id
+    pub other_task_id: Option<OtherTaskId>,
+    /// Kinds of commits which are left untouched
+    pub skip: Option<Vec<SkipRule>>,
+    /// Template of the branch name created by `new-branch` command
+    pub branch_template: Option<String>,
+    /// Take branch name of detached HEAD from `git name-rev`
+    pub detached_name_rev: Option<bool>,
+    /// Pre-fill empty commit message on `prepare-commit-msg` stage
+    pub prefill_empty: Option<bool>,
+    /// Rules turning task id into its canonical form
+    pub normalize: Option<Normalization>,
+    /// Canonical project key by alias or legacy key
+    pub aliases: Option<HashMap<String, String>>,
+}
+
+/// Deserialize option that may be set either as a string or as an array
+fn deserialize_one_or_many<'de, D>(
+    deserializer: D,
+) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => Some(vec![value]),
+        OneOrMany::Many(values) => Some(values),
+    })
+}
+
+impl FileConfig {
+    /// Return config where options from `overrides` take precedence
+    ///
+    /// * `overrides` - config which values should replace current ones
+    pub fn merge(self, overrides: FileConfig) -> FileConfig {
+        FileConfig {
+            task_regex: overrides.task_regex.or(self.task_regex),
+            template: overrides.template.or(self.template),
+            all_task_ids: overrides.all_task_ids.or(self.all_task_ids),
+            task_id_separator: overrides
+                .task_id_separator
+                .or(self.task_id_separator),
+            require: overrides.require.or(self.require),
+            task_id_regex: overrides.task_id_regex.or(self.task_id_regex),
+            exempt_branches: overrides
+                .exempt_branches
+                .or(self.exempt_branches),
+            trailer: overrides.trailer.or(self.trailer),
+            conventional: overrides.conventional.or(self.conventional),
+            non_conventional: overrides
+                .non_conventional
+                .or(self.non_conventional),
+            ignore_case: overrides.ignore_case.or(self.ignore_case),
+            other_task_id: overrides.other_task_id.or(self.other_task_id),
+            skip: overrides.skip.or(self.skip),
+            branch_template: overrides
+                .branch_template
+                .or(self.branch_template),
+            detached_name_rev: overrides
+                .detached_name_rev
+                .or(self.detached_name_rev),
+            prefill_empty: overrides.prefill_empty.or(self.prefill_empty),
+            normalize: match (self.normalize, overrides.normalize) {
+                (Some(normalize), Some(overrides)) => {
+                    Some(normalize.merge(overrides))
+                }
+                (normalize, overrides) => overrides.or(normalize),
+            },
+            aliases: match (self.aliases, overrides.aliases) {
+                (Some(mut aliases), Some(overrides)) => {
+                    aliases.extend(overrides);
+                    Some(aliases)
+                }
+                (aliases, overrides) => overrides.or(aliases),
+            },
+        }
+    }
+}
+
+/// Validated configuration used to provide task id into commit message
+#[derive(Debug)]
+pub struct Config {
+    /// Task regexes in priority order, the first matching one wins
+    pub task_regexes: Vec<Regex>,
+    pub template: String,
+    pub all_task_ids: bool,
+    pub task_id_separator: String,
+    pub require: bool,
+    /// Regexes used to find task id in commit message, see
+    /// [`Config::task_id_regexes`]
+    task_id_regexes: Vec<Regex>,
+    /// Pattern of `task_template` group which would match unrelated text
+    ambiguous_task_id_pattern: Option<String>,
+    pub exempt_branches: Vec<String>,
+    /// Key of the trailer, template is not used if it is set
+    pub trailer: Option<String>,
+    /// Where task id is inserted into Conventional Commits subject, template
+    /// is not used if it is set
+    pub conventional: Option<Placement>,
+    pub non_conventional: NonConventional,
+    /// Find task id in commit message ignoring case
+    pub ignore_case: bool,
+    pub other_task_id: OtherTaskId,
+    /// Kinds of commits which are left untouched
+    pub skip_rules: Vec<SkipRule>,
+    /// Template of the branch name with `task_id`, `slug` and `title`
+    /// placeholders
+    pub branch_template: String,
+    /// Take branch name of detached HEAD from `git name-rev`, off by
+    /// default as any branch containing the commit may be found
+    pub detached_name_rev: bool,
+    /// Pre-fill empty commit message on `prepare-commit-msg` stage, off by
+    /// default as git doesn't abort the commit with non-empty message
+    pub prefill_empty: bool,
+    /// Rules applied to task id retrieved from branch name
+    pub normalization: Normalization,
+    /// Canonical project key by alias, resolved after normalization
+    pub aliases: HashMap<String, String>,
+}
+
+/// Default template of the commit message
+pub const DEFAULT_TEMPLATE: &str = "{subject}\n\n{body}\n\n{task_id}";
+
+/// Default template of the branch name created from task id and title
+pub const DEFAULT_BRANCH_TEMPLATE: &str = "feature/{task_id}-{slug}";
+
+/// Default string used to join several task ids
+pub const DEFAULT_TASK_ID_SEPARATOR: &str = ", ";
+
+/// Branches that don't require task id by default
+pub const DEFAULT_EXEMPT_BRANCHES: [&str; 4] =
+    ["main", "master", "develop", "release/*"];
+
+/// Compile every regex, reporting the first incorrect one
+///
+/// * `regexes` - raw regexes
+fn compile_regexes<S: AsRef<str>>(
+    regexes: &[S],
+) -> Result<Vec<Regex>, ConfigError> {
+    regexes
+        .iter()
+        .map(|regex| {
+            let regex = regex.as_ref();
+            Regex::new(regex).map_err(|err| {
+                ConfigError::InvalidRegex(regex.to_string(), err)
+            })
+        })
+        .collect()
+}
+
+/// Return pattern of `task_template` named capturing group of the regex
+///
+/// Leading inline flags of the regex like `(?i)` are kept in the pattern.
+///
+/// * `task_regex` - raw regex with `task_template` named capturing group
+fn task_template_pattern(task_regex: &str) -> Option<String> {
+    let start = ["(?P<task_template>", "(?<task_template>"]
+        .iter()
+        .find_map(|group| {
+            task_regex.find(group).map(|index| index + group.len())
+        })?;
+
+    let mut depth = 0;
+    let mut escaped = false;
+    let mut in_class = false;
+    for (index, char) in task_regex[start..].char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+
+        match char {
+            '\\' => escaped = true,
+            '[' if !in_class => in_class = true,
+            ']' if in_class => in_class = false,
+            '(' if !in_class => depth += 1,
+            ')' if !in_class && depth == 0 => {
+                let pattern = &task_regex[start..start + index];
+                return Some(format!(
+                    "{}{pattern}",
+                    leading_flags(task_regex)
+                ));
+            }
+            ')' if !in_class => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Return inline flag groups like `(?i)` the regex starts with
+///
+/// * `regex` - raw regex
+fn leading_flags(regex: &str) -> &str {
+    let mut end = 0;
+    while regex[end..].starts_with("(?") {
+        match regex[end + 2..].find(|char: char| !char.is_ascii_alphabetic()) {
+            Some(index) if regex[end + 2 + index..].starts_with(')') => {
+                end += index + 3;
+            }
+            _ => break,
+        }
+    }
+    &regex[..end]
+}
+
+/// Check if the regex matches at least one fixed character
+///
+/// Patterns like `[0-9]+` or `\d+` would take any number for a task id.
+///
+/// * `regex` - raw regex
+fn has_literal(regex: &str) -> bool {
+    let mut chars = regex.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            // Escaped letters are classes or assertions like `\d` or `\b`
+            '\\' => {
+                if chars.next().is_some_and(|char| !char.is_alphanumeric()) {
+                    return true;
+                }
+            }
+            '[' => {
+                chars.next_if_eq(&'^');
+                chars.next_if_eq(&']');
+                while let Some(char) = chars.next() {
+                    match char {
+                        '\\' => {
+                            chars.next();
+                        }
+                        ']' => break,
+                        _ => {}
+                    }
+                }
+            }
+            // Group prefixes like `(?:`, `(?i)` or `(?P<name>`
+            '(' if chars.next_if_eq(&'?').is_some() => {
+                for char in chars.by_ref() {
+                    if matches!(char, ':' | ')' | '>') {
+                        break;
+                    }
+                }
+            }
+            '{' => {
+                for char in chars.by_ref() {
+                    if char == '}' {
+                        break;
+                    }
+                }
+            }
+            '(' | ')' | '|' | '?' | '*' | '+' | '.' | '^' | '$' => {}
+            _ => return true,
+        }
+    }
+    false
+}
+
+/// Convert branch name pattern with `*` wildcards into regex
+///
+/// `*` matches any characters except `/` and `**` matches any characters.
+///
+/// * `pattern` - branch name pattern like `release/*`
+fn branch_pattern_to_regex(pattern: &str) -> Regex {
+    let regex = regex::escape(pattern)
+        .replace(r"\*\*", ".*")
+        .replace(r"\*", "[^/]*");
+
+    Regex::new(&format!("^{regex}$")).expect("Escaped pattern is valid.")
+}
+
+impl Config {
+    /// Create config from raw task regexes and commit message template
+    ///
+    /// * `task_regexes` - regexes with `task_template` named capturing group
+    /// * `template` - commit message template, `\n` escapes are allowed
+    pub fn new<S: AsRef<str>>(
+        task_regexes: &[S],
+        template: &str,
+    ) -> Result<Self, ConfigError> {
+        Config::with_task_id_regexes(task_regexes, template, None)
+    }
+
+    /// Create config, taking task id regexes from `task_template` groups
+    /// unless they are set explicitly
+    ///
+    /// * `task_regexes` - regexes with `task_template` named capturing group
+    /// * `template` - commit message template, `\n` escapes are allowed
+    /// * `task_id_regexes` - regexes searching task id in commit message
+    fn with_task_id_regexes<S: AsRef<str>>(
+        task_regexes: &[S],
+        template: &str,
+        task_id_regexes: Option<&[String]>,
+    ) -> Result<Self, ConfigError> {
+        if task_regexes.is_empty() {
+            return Err(ConfigError::MissingOption("task-regex"));
+        }
+
+        let (task_id_regexes, ambiguous_task_id_pattern) =
+            match task_id_regexes {
+                Some(task_id_regexes) => {
+                    (compile_regexes(task_id_regexes)?, None)
+                }
+                None => {
+                    let patterns = task_regexes
+                        .iter()
+                        .filter_map(|task_regex| {
+                            task_template_pattern(task_regex.as_ref())
+                        })
+                        .collect::<Vec<_>>();
+                    let ambiguous_pattern = patterns
+                        .iter()
+                        .find(|pattern| !has_literal(pattern))
+                        .cloned();
+                    (compile_regexes(&patterns)?, ambiguous_pattern)
+                }
+            };
+        let task_regexes = compile_regexes(task_regexes)?;
+
+        // Remove escaping for commit message template
+        let template = template.replace("\\n", "\n");
+        Template::parse(&template).map_err(ConfigError::InvalidTemplate)?;
+
+        Ok(Config {
+            task_regexes,
+            template,
+            all_task_ids: false,
+            task_id_separator: String::from(DEFAULT_TASK_ID_SEPARATOR),
+            require: false,
+            task_id_regexes,
+            ambiguous_task_id_pattern,
+            exempt_branches: DEFAULT_EXEMPT_BRANCHES
+                .iter()
+                .map(|branch| branch.to_string())
+                .collect(),
+            trailer: None,
+            conventional: None,
+            non_conventional: NonConventional::default(),
+            ignore_case: false,
+            other_task_id: OtherTaskId::default(),
+            skip_rules: DEFAULT_SKIP_RULES.to_vec(),
+            branch_template: String::from(DEFAULT_BRANCH_TEMPLATE),
+            detached_name_rev: false,
+            prefill_empty: false,
+            normalization: Normalization::default(),
+            aliases: HashMap::new(),
+        })
+    }
+
+    /// Return regexes used to find arbitrary task id in commit message
+    ///
+    /// Pattern of `task_template` group without literal characters, e.g.
+    /// `[0-9]+`, would take any number for task id, so `task-id-regex` must
+    /// be set explicitly. Inserting task id from branch name doesn't need
+    /// these regexes.
+    pub fn task_id_regexes(&self) -> Result<&[Regex], ConfigError> {
+        match &self.ambiguous_task_id_pattern {
+            Some(pattern) => {
+                Err(ConfigError::AmbiguousTaskIdRegex(pattern.clone()))
+            }
+            None => Ok(&self.task_id_regexes),
+        }
+    }
+
+    /// Check if branch doesn't require task id
+    ///
+    /// * `branch_name` - name of the branch to check
+    pub fn is_exempt_branch(&self, branch_name: &str) -> bool {
+        self.exempt_branches.iter().any(|pattern| {
+            branch_pattern_to_regex(pattern).is_match(branch_name)
+        })
+    }
+}
+
+impl TryFrom<FileConfig> for Config {
+    type Error = ConfigError;
+
+    fn try_from(raw: FileConfig) -> Result<Self, Self::Error> {
+        let task_regexes = raw
+            .task_regex
+            .ok_or(ConfigError::MissingOption("task-regex"))?;
+        let template = raw.template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+
+        let mut config = Config::with_task_id_regexes(
+            &task_regexes,
+            template,
+            raw.task_id_regex.as_deref(),
+        )?;
+        config.all_task_ids = raw.all_task_ids.unwrap_or_default();
+        if let Some(separator) = raw.task_id_separator {
+            config.task_id_separator = separator.replace("\\n", "\n");
+        }
+        config.require = raw.require.unwrap_or_default();
+        if let Some(exempt_branches) = raw.exempt_branches {
+            config.exempt_branches = exempt_branches;
+        }
+        if let Some(trailer) = raw.trailer {
+            let is_token = !trailer.is_empty()
+                && trailer
+                    .chars()
+                    .all(|char| char.is_ascii_alphanumeric() || char == '-');
+            if !is_token {
+                return Err(ConfigError::InvalidTrailer(trailer));
+            }
+            config.trailer = Some(trailer);
+        }
+        if raw.conventional.is_some() && config.trailer.is_some() {
+            return Err(ConfigError::ConflictingOptions(
+                "conventional",
+                "trailer",
+            ));
+        }
+        config.conventional = raw.conventional;
+        config.non_conventional = raw.non_conventional.unwrap_or_default();
+        config.ignore_case = raw.ignore_case.unwrap_or_default();
+        config.other_task_id = raw.other_task_id.unwrap_or_default();
+        if let Some(skip_rules) = raw.skip {
+            config.skip_rules = skip_rules;
+        }
+        if let Some(branch_template) = raw.branch_template {
+            Template::parse(&branch_template)
+                .map_err(ConfigError::InvalidTemplate)?;
+            config.branch_template = branch_template;
+        }
+        config.detached_name_rev = raw.detached_name_rev.unwrap_or_default();
+        config.prefill_empty = raw.prefill_empty.unwrap_or_default();
+        config.normalization = raw.normalize.unwrap_or_default();
+        config.aliases = raw.aliases.unwrap_or_default();
+        if config.ignore_case {
+            config.task_id_regexes = config
+                .task_id_regexes
+                .iter()
+                .map(|regex| {
+                    RegexBuilder::new(regex.as_str())
+                        .case_insensitive(true)
+                        .build()
+                        .expect("Regex is already validated.")
+                })
+                .collect();
+        }
+
+        Ok(config)
+    }
+}
+
+/// Read config from the given file
+///
+/// Both standalone config and `pyproject.toml` are supported. `None` is
+/// returned if `pyproject.toml` doesn't contain `[tool.pyrust-task-id]`.
+///
+/// * `path` - path to config file
+pub fn read_config_file(
+    path: &Path,
+) -> Result<Option<FileConfig>, ConfigError> {
+    let content = read_to_string(path)
+        .map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+    let parse_error = |err| ConfigError::Parse(path.to_path_buf(), err);
+
+    if path
+        .file_name()
+        .is_some_and(|name| name == PYPROJECT_FILE_NAME)
+    {
+        let mut pyproject: toml::Table =
+            toml::from_str(&content).map_err(parse_error)?;
+
+        let table = pyproject.remove("tool").and_then(|tool| match tool {
+            toml::Value::Table(mut tool) => tool.remove(PYPROJECT_TABLE_NAME),
+            _ => None,
+        });
+
+        match table {
+            Some(table) => table.try_into().map(Some).map_err(parse_error),
+            None => Ok(None),
+        }
+    } else {
+        toml::from_str(&content).map(Some).map_err(parse_error)
+    }
+}
+
+/// Find and read config walking up from the given directory
+///
+/// In every directory `.pyrust-task-id.toml` is checked first and then
+/// `pyproject.toml` with `[tool.pyrust-task-id]` table. Search goes past
+/// the worktree root, so config in a directory holding several repositories
+/// is shared by all of them.
+///
+/// * `start` - directory to start search from
+pub fn discover_config(
+    start: &Path,
+) -> Result<Option<(PathBuf, FileConfig)>, ConfigError> {
+    for directory in start.ancestors() {
+        for file_name in [CONFIG_FILE_NAME, PYPROJECT_FILE_NAME] {
+            let path = directory.join(file_name);
+            if !path.is_file() {
+                continue;
+            }
+
+            if let Some(config) = read_config_file(&path)? {
+                return Ok(Some((path, config)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir, write};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_discover_standalone_config_in_parent_directory() {
+        let root = tempdir().unwrap();
+        let nested = root.path().join("nested");
+        create_dir(&nested).unwrap();
+        write(
+            root.path().join(CONFIG_FILE_NAME),
+            "task-regex = 'feature/(?P<task_template>ABC-\\d+)'\n\
+             template = \"{subject}\\n\\n{task_id}\"\n",
+        )
+        .unwrap();
+
+        let (path, config) = discover_config(&nested).unwrap().unwrap();
+
+        assert_eq!(path, root.path().join(CONFIG_FILE_NAME));
+        assert_eq!(
+            config.task_regex,
+            Some(vec![String::from(r"feature/(?P<task_template>ABC-\d+)")])
+        );
+        assert_eq!(config.template.as_deref(), Some("{subject}\n\n{task_id}"));
+    }
+
+    #[test]
+    fn test_discover_config_shared_by_repositories() {
+        let root = tempdir().unwrap();
+        let repository = root.path().join("repository");
+        create_dir(&repository).unwrap();
+        create_dir(repository.join(".git")).unwrap();
+        write(
+            root.path().join(CONFIG_FILE_NAME),
+            "task-regex = 'shared'\n",
+        )
+        .unwrap();
+
+        let (path, config) = discover_config(&repository).unwrap().unwrap();
+
+        assert_eq!(path, root.path().join(CONFIG_FILE_NAME));
+        assert_eq!(config.task_regex, Some(vec![String::from("shared")]));
+    }
+
+    #[test]
+    fn test_discover_config_in_pyproject() {
+        let root = tempdir().unwrap();
+        write(
+            root.path().join(PYPROJECT_FILE_NAME),
+            "[project]\nname = 'test'\n\n\
+             [tool.pyrust-task-id]\ntemplate = '{subject} {task_id}'\n",
+        )
+        .unwrap();
+
+        let (_, config) = discover_config(root.path()).unwrap().unwrap();
+
+        assert_eq!(config.template.as_deref(), Some("{subject} {task_id}"));
+        assert_eq!(config.task_regex, None);
+    }
+
+    #[test]
+    fn test_pyproject_without_table_is_skipped() {
+        let root = tempdir().unwrap();
+        write(
+            root.path().join(PYPROJECT_FILE_NAME),
+            "[project]\nname = 'test'\n",
+        )
+        .unwrap();
+
+        assert!(read_config_file(&root.path().join(PYPROJECT_FILE_NAME))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_config_error_source() {
+        use std::error::Error;
+
+        let root = tempdir().unwrap();
+        let err =
+            read_config_file(&root.path().join(CONFIG_FILE_NAME)).unwrap_err();
+
+        assert!(err.source().is_some_and(|source| source.is::<io::Error>()));
+        assert!(crate::Error::from(err).source().is_some());
+    }
+
+    #[test]
+    fn test_unknown_option_is_rejected() {
+        let root = tempdir().unwrap();
+        let path = root.path().join(CONFIG_FILE_NAME);
+        write(&path, "task-regexp = 'typo'\n").unwrap();
+
+        assert!(matches!(
+            read_config_file(&path),
+            Err(ConfigError::Parse(..))
+        ));
+    }
+
+    #[test]
+    fn test_cli_options_override_file_options() {
+        let file_config = FileConfig {
+            task_regex: Some(vec![String::from("file")]),
+            template: Some(String::from("{task_id}")),
+            ..Default::default()
+        };
+        let cli_config = FileConfig {
+            task_regex: Some(vec![String::from("cli")]),
+            ..Default::default()
+        };
+
+        let config = file_config.merge(cli_config);
+
+        assert_eq!(config.task_regex, Some(vec![String::from("cli")]));
+        assert_eq!(config.template.as_deref(), Some("{task_id}"));
+    }
+
+    #[test]
+    fn test_normalize_table_is_merged_by_rule() {
+        let root = tempdir().unwrap();
+        let path = root.path().join(CONFIG_FILE_NAME);
+        write(&path, "[normalize]\ncase = 'upper'\nkey-separator = '-'\n")
+            .unwrap();
+        let cli_config = FileConfig {
+            normalize: Some(Normalization {
+                key_separator: Some(String::from("_")),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let config =
+            read_config_file(&path).unwrap().unwrap().merge(cli_config);
+
+        assert_eq!(
+            config.normalize,
+            Some(Normalization {
+                case: Some(crate::normalize::Case::Upper),
+                key_separator: Some(String::from("_")),
+                number_width: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_without_task_regex_is_invalid() {
+        let raw = FileConfig {
+            template: Some(String::from("{task_id}")),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            Config::try_from(raw),
+            Err(ConfigError::MissingOption("task-regex"))
+        ));
+    }
+
+    #[test]
+    fn test_config_without_template_uses_default_one() {
+        let raw = FileConfig {
+            task_regex: Some(vec![String::from(".*")]),
+            ..Default::default()
+        };
+
+        let config = Config::try_from(raw).unwrap();
+
+        assert_eq!(config.template, "{subject}\n\n{body}\n\n{task_id}");
+    }
+
+    #[test]
+    fn test_task_id_regex_is_taken_from_task_template_group() {
+        let config = Config::new(
+            &[r"feature/(?P<task_template>(ABC|XYZ)-[0-9()]+)-(?<slug>.*)"],
+            "{task_id}",
+        )
+        .unwrap();
+
+        assert_eq!(config.task_id_regexes[0].as_str(), r"(ABC|XYZ)-[0-9()]+");
+    }
+
+    #[test]
+    fn test_task_id_regex_keeps_inline_flags() {
+        let config =
+            Config::new(&[r"(?i)feature/(?P<task_template>abc-\d+)"], "")
+                .unwrap();
+
+        assert_eq!(config.task_id_regexes[0].as_str(), r"(?i)abc-\d+");
+        assert!(config.task_id_regexes[0].is_match("Fix ABC-5"));
+    }
+
+    #[test]
+    fn test_task_id_regex_without_literal_must_be_explicit() {
+        for task_regex in [
+            r"gh-(?P<task_template>\d+)",
+            r"(?P<task_template>[0-9]{2,})",
+            r"(?i)(?P<task_template>(?:\d+|\w+))",
+        ] {
+            // Config is still valid, task id may be inserted
+            let config = Config::new(&[task_regex], "").unwrap();
+            assert!(matches!(
+                config.task_id_regexes(),
+                Err(ConfigError::AmbiguousTaskIdRegex(..))
+            ));
+        }
+
+        let raw = FileConfig {
+            task_regex: Some(vec![String::from(r"gh-(?P<task_template>\d+)")]),
+            task_id_regex: Some(vec![String::from(r"#\d+")]),
+            ..Default::default()
+        };
+        let config = Config::try_from(raw).unwrap();
+
+        assert_eq!(config.task_id_regexes().unwrap()[0].as_str(), r"#\d+");
+        let config = Config::new(&[r"(?P<task_template>\#\d+)"], "").unwrap();
+        assert!(config.task_id_regexes().is_ok());
+    }
+
+    #[test]
+    fn test_exempt_branches() {
+        let config = Config::new(&[".*"], "{task_id}").unwrap();
+
+        assert!(config.is_exempt_branch("main"));
+        assert!(config.is_exempt_branch("release/1.2"));
+        assert!(!config.is_exempt_branch("release/1.2/hotfix"));
+        assert!(!config.is_exempt_branch("hotfix-foo"));
+    }
+
+    #[test]
+    fn test_config_with_incorrect_regex_is_invalid() {
+        assert!(matches!(
+            Config::new(&["(?P<task_template>"], "{task_id}"),
+            Err(ConfigError::InvalidRegex(..))
+        ));
+    }
+
+    #[test]
+    fn test_task_regex_may_be_set_as_array() {
+        let root = tempdir().unwrap();
+        let path = root.path().join(CONFIG_FILE_NAME);
+        write(&path, "task-regex = ['first', 'second']\n").unwrap();
+
+        let config = read_config_file(&path).unwrap().unwrap();
+
+        assert_eq!(
+            config.task_regex,
+            Some(vec![String::from("first"), String::from("second")])
+        );
+    }
+
+    #[test]
+    fn test_task_id_separator_escapes_are_removed() {
+        let raw = FileConfig {
+            task_regex: Some(vec![String::from(".*")]),
+            template: Some(String::from("{task_id}")),
+            all_task_ids: Some(true),
+            task_id_separator: Some(String::from("\\nRefs: ")),
+            ..Default::default()
+        };
+
+        let config = Config::try_from(raw).unwrap();
+
+        assert!(config.all_task_ids);
+        assert_eq!(config.task_id_separator, "\nRefs: ");
+    }
+}
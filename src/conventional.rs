@@ -0,0 +1,164 @@
+//! Subjects following Conventional Commits
+//!
+//! Subject is expected in `type(scope)!: description` form where scope and
+//! breaking change marker are optional.
+
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt;
+
+/// Return regex of Conventional Commits subject
+fn get_subject_regex() -> Regex {
+    Regex::new(
+        r"^(?P<type>[A-Za-z][\w-]*)(?:\((?P<scope>[^()\r\n]*)\))?(?P<breaking>!)?: (?P<description>\S.*)$",
+    )
+    .unwrap()
+}
+
+/// String between existing scope and task ids appended to it as well as
+/// between several task ids, subject must stay on one line
+const SCOPE_SEPARATOR: &str = ", ";
+
+/// Where task id is inserted into Conventional Commits subject
+#[derive(Deserialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Placement {
+    /// Task id replaces the scope: `feat(ABC-1): description`
+    Scope,
+    /// Task id is appended to the scope: `feat(api, ABC-1): description`
+    AppendScope,
+    /// Task id prefixes the description: `feat(api): ABC-1 description`
+    Prefix,
+}
+
+/// What to do with subject which doesn't follow Conventional Commits
+#[derive(
+    Deserialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Default,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum NonConventional {
+    /// Leave commit message untouched
+    #[default]
+    Skip,
+    /// Fail with error
+    Fail,
+}
+
+/// Parsed Conventional Commits subject
+#[derive(Debug, PartialEq)]
+pub struct ConventionalSubject {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+}
+
+impl ConventionalSubject {
+    /// Parse subject, `None` is returned if it isn't conventional
+    ///
+    /// * `subject` - subject of the commit message
+    pub fn parse(subject: &str) -> Option<Self> {
+        let captures = get_subject_regex().captures(subject)?;
+
+        Some(ConventionalSubject {
+            kind: captures["type"].to_string(),
+            scope: captures
+                .name("scope")
+                .map(|scope| scope.as_str().to_string()),
+            breaking: captures.name("breaking").is_some(),
+            description: captures["description"].to_string(),
+        })
+    }
+
+    /// Return subject with task ids inserted into it
+    ///
+    /// * `placement` - where task ids are inserted
+    /// * `task_ids` - task ids to insert
+    pub fn with_task_ids(
+        mut self,
+        placement: Placement,
+        task_ids: &[String],
+    ) -> Self {
+        let task_id = task_ids.join(SCOPE_SEPARATOR);
+        match placement {
+            Placement::Scope => self.scope = Some(task_id),
+            Placement::AppendScope => {
+                self.scope = Some(match self.scope {
+                    Some(scope) if !scope.trim().is_empty() => {
+                        format!("{scope}{SCOPE_SEPARATOR}{task_id}")
+                    }
+                    _ => task_id,
+                })
+            }
+            Placement::Prefix => {
+                self.description = format!("{task_id} {}", self.description)
+            }
+        }
+
+        self
+    }
+}
+
+impl fmt::Display for ConventionalSubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(scope) = &self.scope {
+            write!(f, "({scope})")?;
+        }
+        if self.breaking {
+            write!(f, "!")?;
+        }
+        write!(f, ": {}", self.description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conventional_subject() {
+        assert_eq!(
+            ConventionalSubject::parse("feat(api)!: drop v1 endpoints"),
+            Some(ConventionalSubject {
+                kind: String::from("feat"),
+                scope: Some(String::from("api")),
+                breaking: true,
+                description: String::from("drop v1 endpoints"),
+            })
+        );
+        assert_eq!(
+            ConventionalSubject::parse("fix: typo").unwrap().scope,
+            None
+        );
+        assert_eq!(ConventionalSubject::parse("Fix typo"), None);
+        assert_eq!(ConventionalSubject::parse("fix(api):typo"), None);
+    }
+
+    #[test]
+    fn test_insert_task_id() {
+        let insert = |subject, placement| {
+            ConventionalSubject::parse(subject)
+                .unwrap()
+                .with_task_ids(placement, &[String::from("ABC-1")])
+                .to_string()
+        };
+
+        assert_eq!(
+            insert("feat(api)!: x", Placement::Scope),
+            "feat(ABC-1)!: x"
+        );
+        assert_eq!(
+            insert("feat(api): x", Placement::AppendScope),
+            "feat(api, ABC-1): x"
+        );
+        assert_eq!(
+            insert("feat: x", Placement::AppendScope),
+            "feat(ABC-1): x"
+        );
+        assert_eq!(
+            insert("feat(api): x", Placement::Prefix),
+            "feat(api): ABC-1 x"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use crate::config::ConfigError;
+use crate::template::TemplateError;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Reason why task id can't be retrieved from branch name
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum TaskIDError {
+    NotInBranch,
+    WrongCapturingGroup,
+}
+
+impl fmt::Display for TaskIDError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskIDError::NotInBranch => {
+                write!(f, "Branch name doesn't match task regex.")
+            }
+            TaskIDError::WrongCapturingGroup => write!(
+                f,
+                "Make sure you included capturing group with name `task_template`."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TaskIDError {}
+
+/// Error which may occur while providing task id into commit message
+#[derive(Debug)]
+pub enum Error {
+    /// Task id can't be retrieved from branch name
+    TaskId(TaskIDError),
+    /// Task id is required but it is missing, contains explanation
+    MissingTaskId(String),
+    /// Git is not available or returned unexpected output
+    Git(String),
+    /// Unable to read or write the file
+    Io(PathBuf, io::Error),
+    /// Message template is incorrect
+    Template(TemplateError),
+    /// Regex is incorrect
+    Regex(String, regex::Error),
+    /// Config is incorrect
+    Config(ConfigError),
+    /// Subject doesn't follow Conventional Commits, contains the subject
+    NotConventional(String),
+    /// Commit message contains task id other than the one from branch name,
+    /// contains explanation
+    OtherTaskId(String),
+    /// Commit can't be rewritten safely without force, contains explanation
+    UnsafeRewrite(String),
+}
+
+impl Error {
+    /// Return exit code the binary should finish with
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Template(..) | Error::Regex(..) | Error::Config(..) => 2,
+            Error::TaskId(TaskIDError::NotInBranch) => 3,
+            Error::TaskId(TaskIDError::WrongCapturingGroup) => 4,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::TaskId(err) => write!(f, "{err}"),
+            Error::MissingTaskId(message) => write!(f, "{message}"),
+            Error::OtherTaskId(message) => write!(f, "{message}"),
+            Error::UnsafeRewrite(message) => write!(f, "{message}"),
+            Error::Git(message) => write!(f, "{message}"),
+            Error::Io(path, err) => {
+                write!(f, "Unable to access `{}`: {err}", path.display())
+            }
+            Error::Template(err) => write!(f, "{err}"),
+            Error::Regex(regex, err) => {
+                write!(f, "Make sure regex `{regex}` is correct: {err}")
+            }
+            Error::Config(err) => write!(f, "{err}"),
+            Error::NotConventional(subject) => write!(
+                f,
+                "Subject `{subject}` doesn't follow Conventional Commits, expected `type(scope): description`."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::TaskId(err) => Some(err),
+            Error::Io(_, err) => Some(err),
+            Error::Regex(_, err) => Some(err),
+            Error::Template(err) => Some(err),
+            Error::Config(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<TaskIDError> for Error {
+    fn from(err: TaskIDError) -> Self {
+        Error::TaskId(err)
+    }
+}
+
+impl From<TemplateError> for Error {
+    fn from(err: TemplateError) -> Self {
+        Error::Template(err)
+    }
+}
+
+impl From<ConfigError> for Error {
+    fn from(err: ConfigError) -> Self {
+        match err {
+            ConfigError::InvalidRegex(regex, err) => Error::Regex(regex, err),
+            ConfigError::InvalidTemplate(err) => Error::Template(err),
+            err => Error::Config(err),
+        }
+    }
+}
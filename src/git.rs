@@ -0,0 +1,758 @@
+//! Access to git state
+//!
+//! Repository is read in-process first. If that fails, e.g. because of
+//! unsupported repository format, `git` command is used as a fallback.
+
+use crate::Error;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// Comment string git uses when it isn't set in config
+const DEFAULT_COMMENT_STRING: &str = "#";
+
+/// Config options with comment string in priority order
+const COMMENT_STRING_OPTIONS: [&str; 2] =
+    ["core.commentString", "core.commentChar"];
+
+/// Run git with the given arguments and return its output
+///
+/// * `args` - arguments of git command
+fn run_git(args: &[&str]) -> Result<Output, Error> {
+    Command::new("git").args(args).output().map_err(|err| {
+        Error::Git(format!(
+            "Make sure git is installed and git repo exists: {err}"
+        ))
+    })
+}
+
+/// Return stdout of git command as text
+///
+/// * `output` - output of git command
+fn get_output_text(output: Output) -> Result<String, Error> {
+    String::from_utf8(output.stdout)
+        .map_err(|_| Error::Git(String::from("Got non utf-8 chars from git.")))
+}
+
+/// Open repository containing the given directory
+///
+/// `GIT_DIR` and other git environment variables are taken into account.
+///
+/// * `directory` - directory to start repository discovery from
+fn open_repository(directory: &Path) -> Result<gix::Repository, Error> {
+    gix::discover_with_environment_overrides(directory).map_err(|err| {
+        Error::Git(format!("Unable to open git repository: {err}"))
+    })
+}
+
+/// Return comment string from config value
+///
+/// `auto` means git picks a character not used in the message, the default
+/// one is used in this case as the message is not known yet.
+///
+/// * `value` - value of `core.commentString` or `core.commentChar`
+fn normalize_comment_string(value: &str) -> Option<String> {
+    match value.trim_end_matches(['\r', '\n']) {
+        "" => None,
+        "auto" => Some(String::from(DEFAULT_COMMENT_STRING)),
+        value => Some(value.to_string()),
+    }
+}
+
+/// Where the current branch name was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchSource {
+    /// Branch checked out in HEAD
+    Head,
+    /// Branch being rebased by merge backend of `git rebase`
+    RebaseMerge,
+    /// Branch being rebased by apply backend of `git rebase` or by `git am`
+    RebaseApply,
+    /// Branch `git bisect` was started from
+    Bisect,
+    /// Local branch containing detached HEAD found by `git name-rev`
+    NameRev,
+    /// HEAD is detached and branch is unknown, name is empty
+    Detached,
+}
+
+impl fmt::Display for BranchSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BranchSource::Head => write!(f, "HEAD"),
+            BranchSource::RebaseMerge => write!(f, "rebase-merge/head-name"),
+            BranchSource::RebaseApply => write!(f, "rebase-apply/head-name"),
+            BranchSource::Bisect => write!(f, "BISECT_START"),
+            BranchSource::NameRev => write!(f, "git name-rev"),
+            BranchSource::Detached => write!(f, "detached HEAD"),
+        }
+    }
+}
+
+/// Files in git dir which keep the original branch while HEAD is detached
+const BRANCH_STATE_FILES: [(&str, BranchSource); 3] = [
+    ("rebase-merge/head-name", BranchSource::RebaseMerge),
+    ("rebase-apply/head-name", BranchSource::RebaseApply),
+    ("BISECT_START", BranchSource::Bisect),
+];
+
+/// Return branch checked out in HEAD and git dir reading the repository
+/// in-process
+///
+/// * `directory` - directory inside of the repository
+fn read_head(directory: &Path) -> Result<(Option<String>, PathBuf), Error> {
+    let repository = open_repository(directory)?;
+    let head_name = repository.head_name().map_err(|err| {
+        Error::Git(format!("Unable to read HEAD of the repository: {err}"))
+    })?;
+
+    Ok((
+        head_name.map(|name| name.shorten().to_string()),
+        repository.git_dir().to_path_buf(),
+    ))
+}
+
+/// Return branch checked out in HEAD and git dir using `git` command
+fn get_head_with_command() -> Result<(Option<String>, PathBuf), Error> {
+    let output = run_git(&["branch", "--show-current"])?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "Unable to get current branch, make sure git repo exists: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let branch = String::from(get_output_text(output)?.trim());
+
+    let output = run_git(&["rev-parse", "--absolute-git-dir"])?;
+    let git_dir = PathBuf::from(get_output_text(output)?.trim());
+
+    Ok(((!branch.is_empty()).then_some(branch), git_dir))
+}
+
+/// Return branch saved by rebase or bisect in progress
+///
+/// Rebase saves full ref name or `detached HEAD`, bisect saves short branch
+/// name or object id of detached HEAD.
+///
+/// * `git_dir` - git dir of the current worktree
+fn read_state_branch(git_dir: &Path) -> Option<(String, BranchSource)> {
+    BRANCH_STATE_FILES.iter().find_map(|(file_name, source)| {
+        let content = fs::read_to_string(git_dir.join(file_name)).ok()?;
+        let content = content.trim();
+
+        let branch = match source {
+            BranchSource::Bisect => (!content.is_empty()
+                && !content.chars().all(|char| char.is_ascii_hexdigit()))
+            .then_some(content)?,
+            _ => content.strip_prefix("refs/heads/")?,
+        };
+
+        Some((branch.to_string(), *source))
+    })
+}
+
+/// Return local branch containing HEAD using `git name-rev`
+///
+/// Suffix like `~2` is removed from the name, so the branch is returned
+/// even if HEAD points to one of its previous commits.
+fn get_branch_with_name_rev() -> Option<String> {
+    let output = run_git(&[
+        "name-rev",
+        "--name-only",
+        "--no-undefined",
+        "--refs=refs/heads/*",
+        "HEAD",
+    ])
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let name = get_output_text(output).ok()?;
+    let branch = name.trim().split(['~', '^']).next()?;
+
+    (!branch.is_empty()).then(|| branch.to_string())
+}
+
+/// Return current git branch name and where it was taken from
+///
+/// If HEAD is detached, branch is taken from rebase or bisect in progress
+/// and then, if allowed, from `git name-rev`. Empty name is returned if
+/// nothing helps, the same as `git branch --show-current` does.
+///
+/// * `name_rev` - whether to take any local branch containing detached HEAD,
+///   which may belong to another task
+pub fn resolve_current_branch(
+    name_rev: bool,
+) -> Result<(String, BranchSource), Error> {
+    let (head_branch, git_dir) = read_head(Path::new(".")).or_else(|err| {
+        log::debug!("{err} Falling back to `git` command.");
+        get_head_with_command()
+    })?;
+
+    let (branch, source) = match head_branch {
+        Some(branch) => (branch, BranchSource::Head),
+        None => read_state_branch(&git_dir)
+            .or_else(|| {
+                name_rev
+                    .then(get_branch_with_name_rev)
+                    .flatten()
+                    .map(|branch| (branch, BranchSource::NameRev))
+            })
+            .unwrap_or((String::new(), BranchSource::Detached)),
+    };
+    log::debug!("Branch name `{branch}` is taken from {source}.");
+
+    Ok((branch, source))
+}
+
+/// Return current git branch name if the repo exists
+///
+/// * `name_rev` - whether to fall back to `git name-rev` for detached HEAD
+pub fn get_current_branch(name_rev: bool) -> Result<String, Error> {
+    Ok(resolve_current_branch(name_rev)?.0)
+}
+
+/// Return git dir of the current worktree
+pub fn get_git_dir() -> Result<PathBuf, Error> {
+    open_repository(Path::new("."))
+        .map(|repository| repository.git_dir().to_path_buf())
+        .or_else(|err| {
+            log::debug!("{err} Falling back to `git` command.");
+            let output = run_git(&["rev-parse", "--absolute-git-dir"])?;
+            if !output.status.success() {
+                return Err(Error::Git(String::from(
+                    "Unable to get git dir, make sure git repo exists.",
+                )));
+            }
+            Ok(PathBuf::from(get_output_text(output)?.trim()))
+        })
+}
+
+/// Return directory git runs hooks from
+///
+/// `core.hooksPath`, worktrees and `GIT_DIR` are resolved by git itself.
+pub fn get_hooks_dir() -> Result<PathBuf, Error> {
+    let output = run_git(&["rev-parse", "--git-path", "hooks"])?;
+    if !output.status.success() {
+        return Err(Error::Git(String::from(
+            "Unable to get hooks dir, make sure git repo exists.",
+        )));
+    }
+
+    let hooks_dir = PathBuf::from(get_output_text(output)?.trim());
+    if hooks_dir.is_absolute() {
+        return Ok(hooks_dir);
+    }
+    std::env::current_dir()
+        .map(|directory| directory.join(&hooks_dir))
+        .map_err(|err| Error::Io(hooks_dir, err))
+}
+
+/// Return comment string reading config in-process
+///
+/// Includes and worktree config are resolved the same way git does it.
+///
+/// * `directory` - directory inside of the repository
+fn read_comment_string(directory: &Path) -> Result<String, Error> {
+    let repository = open_repository(directory)?;
+    let config = repository.config_snapshot();
+
+    Ok(COMMENT_STRING_OPTIONS
+        .iter()
+        .find_map(|option| {
+            normalize_comment_string(&config.string(*option)?.to_string())
+        })
+        .unwrap_or_else(|| String::from(DEFAULT_COMMENT_STRING)))
+}
+
+/// Return comment string using `git config` command
+fn get_git_comment_string_with_command() -> Result<String, Error> {
+    for option in COMMENT_STRING_OPTIONS {
+        let command_output =
+            get_output_text(run_git(&["config", "--get", option])?)?;
+
+        if let Some(comment_string) = normalize_comment_string(&command_output)
+        {
+            return Ok(comment_string);
+        }
+    }
+
+    Ok(String::from(DEFAULT_COMMENT_STRING))
+}
+
+/// Return comment string which was set in git config
+pub fn get_git_comment_string() -> Result<String, Error> {
+    read_comment_string(Path::new(".")).or_else(|err| {
+        log::debug!("{err} Falling back to `git` command.");
+        get_git_comment_string_with_command()
+    })
+}
+
+/// Format of `git log` output parsed by [`parse_commits`]
+const COMMIT_FORMAT: &str = "--format=%H %T %P%n%an%n%ae%n%ad%n%B";
+
+/// Commit read from the repository
+#[derive(Debug, PartialEq)]
+pub struct Commit {
+    pub sha: String,
+    pub tree: String,
+    pub parents: Vec<String>,
+    pub author_name: String,
+    pub author_email: String,
+    /// Author date in git internal format, e.g. `1700000000 +0100`
+    pub author_date: String,
+    /// Raw commit message
+    pub message: String,
+}
+
+/// Parse `git log -z` output produced with [`COMMIT_FORMAT`]
+///
+/// * `output` - output of `git log`
+fn parse_commits(output: &str) -> Vec<Commit> {
+    output
+        .split('\0')
+        .filter(|record| !record.is_empty())
+        .map(|record| {
+            let mut lines = record.splitn(5, '\n');
+            let mut hashes = lines
+                .next()
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(String::from);
+            let mut next_line =
+                || lines.next().unwrap_or_default().to_string();
+
+            Commit {
+                sha: hashes.next().unwrap_or_default(),
+                tree: hashes.next().unwrap_or_default(),
+                parents: hashes.collect(),
+                author_name: next_line(),
+                author_email: next_line(),
+                author_date: next_line(),
+                message: next_line(),
+            }
+        })
+        .collect()
+}
+
+/// Return commits selected by revisions, parents go before children
+///
+/// * `revisions` - revisions in `git log` syntax, e.g. `origin/main..HEAD`
+pub fn list_commits(revisions: &[&str]) -> Result<Vec<Commit>, Error> {
+    let mut args =
+        vec!["log", "-z", "--reverse", "--topo-order", "--date=raw"];
+    args.push(COMMIT_FORMAT);
+    args.extend(revisions);
+    args.push("--");
+
+    let output = run_git(&args)?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "Unable to list commits of `{}`: {}",
+            revisions.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(parse_commits(&get_output_text(output)?))
+}
+
+/// Return object name the revision points to
+///
+/// * `revision` - revision, e.g. `HEAD`
+pub fn resolve_revision(revision: &str) -> Result<String, Error> {
+    let output = run_git(&["rev-parse", "--verify", "-q", revision])?;
+    if !output.status.success() {
+        return Err(Error::Git(format!("Unknown revision `{revision}`.")));
+    }
+
+    Ok(get_output_text(output)?.trim().to_string())
+}
+
+/// Create copy of the commit with other parents and message
+///
+/// Tree and author are kept, committer is the current user, the same as
+/// `git rebase` does it. Return name of the new commit.
+///
+/// * `commit` - commit to copy
+/// * `parents` - parents of the new commit
+/// * `message` - message of the new commit
+pub fn copy_commit(
+    commit: &Commit,
+    parents: &[String],
+    message: &str,
+) -> Result<String, Error> {
+    let mut args = vec!["commit-tree", commit.tree.as_str()];
+    for parent in parents {
+        args.extend(["-p", parent.as_str()]);
+    }
+    args.extend(["-F", "-"]);
+
+    let git_error =
+        |err| Error::Git(format!("Unable to create commit: {err}"));
+    let mut child = Command::new("git")
+        .args(&args)
+        .env("GIT_AUTHOR_NAME", &commit.author_name)
+        .env("GIT_AUTHOR_EMAIL", &commit.author_email)
+        .env("GIT_AUTHOR_DATE", &commit.author_date)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(git_error)?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(message.as_bytes())
+        .map_err(git_error)?;
+
+    let output = child.wait_with_output().map_err(git_error)?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "Unable to copy commit `{}`: {}",
+            commit.sha,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(get_output_text(output)?.trim().to_string())
+}
+
+/// Create branch from `HEAD` and check it out
+///
+/// * `branch_name` - name of the new branch
+pub fn create_branch(branch_name: &str) -> Result<(), Error> {
+    let output = run_git(&["checkout", "-q", "-b", branch_name])?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "Unable to create branch `{branch_name}`: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Point the reference to the new object if it still points to the old one
+///
+/// * `reference` - reference to update, `HEAD` updates the current branch
+/// * `new_sha` - new value of the reference
+/// * `old_sha` - expected current value of the reference
+/// * `reason` - message written into reflog
+pub fn update_ref(
+    reference: &str,
+    new_sha: &str,
+    old_sha: &str,
+    reason: &str,
+) -> Result<(), Error> {
+    let output =
+        run_git(&["update-ref", "-m", reason, reference, new_sha, old_sha])?;
+    if !output.status.success() {
+        return Err(Error::Git(format!(
+            "Unable to update `{reference}`: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Ref update which is being pushed
+#[derive(Debug, PartialEq)]
+pub struct PushedRef {
+    pub local_ref: String,
+    pub local_sha: String,
+    pub remote_ref: String,
+    pub remote_sha: String,
+}
+
+impl PushedRef {
+    /// Check that remote ref is being deleted
+    pub fn is_deletion(&self) -> bool {
+        is_null_sha(&self.local_sha)
+    }
+
+    /// Check that remote ref doesn't exist yet
+    pub fn is_new(&self) -> bool {
+        is_null_sha(&self.remote_sha)
+    }
+}
+
+/// Ref update passed to `reference-transaction` hook
+#[derive(Debug, PartialEq)]
+pub struct RefUpdate {
+    pub old_sha: String,
+    pub new_sha: String,
+    pub reference: String,
+}
+
+impl RefUpdate {
+    /// Check that the ref is being created
+    pub fn is_creation(&self) -> bool {
+        is_null_sha(&self.old_sha) && !is_null_sha(&self.new_sha)
+    }
+}
+
+/// Check that object name consists of zeros, git uses it for missing object
+///
+/// * `sha` - object name
+fn is_null_sha(sha: &str) -> bool {
+    sha.chars().all(|char| char == '0')
+}
+
+/// Parse lines `pre-push` hook receives on stdin
+///
+/// Every line looks like `<local ref> <local sha> <remote ref> <remote sha>`,
+/// incorrect lines are ignored.
+///
+/// * `input` - stdin of `pre-push` hook
+pub fn parse_pre_push_refs(input: &str) -> Vec<PushedRef> {
+    input
+        .lines()
+        .filter_map(|line| {
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [local_ref, local_sha, remote_ref, remote_sha] => {
+                    Some(PushedRef {
+                        local_ref: local_ref.to_string(),
+                        local_sha: local_sha.to_string(),
+                        remote_ref: remote_ref.to_string(),
+                        remote_sha: remote_sha.to_string(),
+                    })
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Parse lines `reference-transaction` hook receives on stdin
+///
+/// Every line looks like `<old sha> <new sha> <ref>`, incorrect lines are
+/// ignored.
+///
+/// * `input` - stdin of `reference-transaction` hook
+pub fn parse_ref_updates(input: &str) -> Vec<RefUpdate> {
+    input
+        .lines()
+        .filter_map(|line| {
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [old_sha, new_sha, reference] => Some(RefUpdate {
+                    old_sha: old_sha.to_string(),
+                    new_sha: new_sha.to_string(),
+                    reference: reference.to_string(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Run git command in the directory and make sure it succeeded
+    fn git(directory: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(directory)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    /// Create repository with the branch checked out
+    fn init_repository(branch: &str) -> TempDir {
+        let directory = TempDir::new().unwrap();
+        git(directory.path(), &["init", "-q", "-b", branch]);
+        directory
+    }
+
+    #[test]
+    fn test_read_head() {
+        let directory = init_repository("feature/TASK-1-test");
+
+        let (branch, git_dir) = read_head(directory.path()).unwrap();
+        assert_eq!(branch.as_deref(), Some("feature/TASK-1-test"));
+        assert_eq!(
+            git_dir.canonicalize().unwrap(),
+            directory.path().join(".git").canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_head_detached() {
+        let directory = init_repository("main");
+        git(
+            directory.path(),
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        );
+        git(directory.path(), &["checkout", "-q", "--detach"]);
+
+        assert_eq!(read_head(directory.path()).unwrap().0, None);
+    }
+
+    #[test]
+    fn test_read_state_branch() {
+        let git_dir = TempDir::new().unwrap();
+        assert_eq!(read_state_branch(git_dir.path()), None);
+
+        fs::write(git_dir.path().join("BISECT_START"), "TASK-2-bisect\n")
+            .unwrap();
+        assert_eq!(
+            read_state_branch(git_dir.path()),
+            Some((String::from("TASK-2-bisect"), BranchSource::Bisect))
+        );
+
+        fs::create_dir(git_dir.path().join("rebase-apply")).unwrap();
+        fs::write(
+            git_dir.path().join("rebase-apply/head-name"),
+            "refs/heads/TASK-1-apply\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_state_branch(git_dir.path()),
+            Some((String::from("TASK-1-apply"), BranchSource::RebaseApply))
+        );
+
+        fs::create_dir(git_dir.path().join("rebase-merge")).unwrap();
+        fs::write(
+            git_dir.path().join("rebase-merge/head-name"),
+            "refs/heads/feature/TASK-1-merge\n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_state_branch(git_dir.path()),
+            Some((
+                String::from("feature/TASK-1-merge"),
+                BranchSource::RebaseMerge
+            ))
+        );
+    }
+
+    #[test]
+    fn test_read_state_branch_detached() {
+        let git_dir = TempDir::new().unwrap();
+        fs::create_dir(git_dir.path().join("rebase-merge")).unwrap();
+        fs::write(
+            git_dir.path().join("rebase-merge/head-name"),
+            "detached HEAD\n",
+        )
+        .unwrap();
+        fs::write(
+            git_dir.path().join("BISECT_START"),
+            "4b825dc642cb6eb9a060e54bf8d69288fbee4904\n",
+        )
+        .unwrap();
+
+        assert_eq!(read_state_branch(git_dir.path()), None);
+    }
+
+    #[test]
+    fn test_read_comment_string_default() {
+        let directory = init_repository("main");
+
+        assert_eq!(read_comment_string(directory.path()).unwrap(), "#");
+    }
+
+    #[test]
+    fn test_read_comment_string_priority() {
+        let directory = init_repository("main");
+        git(directory.path(), &["config", "core.commentChar", ";"]);
+        assert_eq!(read_comment_string(directory.path()).unwrap(), ";");
+
+        git(directory.path(), &["config", "core.commentString", "//"]);
+        assert_eq!(read_comment_string(directory.path()).unwrap(), "//");
+    }
+
+    #[test]
+    fn test_read_comment_string_from_include() {
+        let directory = init_repository("main");
+        let include = directory.path().join("included.gitconfig");
+        std::fs::write(&include, "[core]\n\tcommentChar = %\n").unwrap();
+        git(
+            directory.path(),
+            &["config", "include.path", include.to_str().unwrap()],
+        );
+
+        assert_eq!(read_comment_string(directory.path()).unwrap(), "%");
+    }
+
+    #[test]
+    fn test_parse_commits() {
+        let output = "aaa ttt ppp qqq\nJane Doe\njane@example.com\n1700000000 +0100\nSubject\n\nBody\n\0bbb ttt\nJ\nj@example.com\n1700000000 +0000\nRoot\n\0";
+
+        let commits = parse_commits(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(
+            commits[0],
+            Commit {
+                sha: String::from("aaa"),
+                tree: String::from("ttt"),
+                parents: vec![String::from("ppp"), String::from("qqq")],
+                author_name: String::from("Jane Doe"),
+                author_email: String::from("jane@example.com"),
+                author_date: String::from("1700000000 +0100"),
+                message: String::from("Subject\n\nBody\n"),
+            }
+        );
+        assert!(commits[1].parents.is_empty());
+        assert_eq!(commits[1].message, "Root\n");
+    }
+
+    #[test]
+    fn test_parse_pre_push_refs() {
+        let zeros = "0".repeat(40);
+        let refs = parse_pre_push_refs(&format!(
+            "refs/heads/ABC-1 {sha} refs/heads/ABC-1 {zeros}\n\
+             (delete) {zeros} refs/heads/old {sha}\n\
+             garbage\n",
+            sha = "a".repeat(40),
+        ));
+
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].local_ref, "refs/heads/ABC-1");
+        assert!(refs[0].is_new() && !refs[0].is_deletion());
+        assert!(refs[1].is_deletion() && !refs[1].is_new());
+    }
+
+    #[test]
+    fn test_parse_ref_updates() {
+        let zeros = "0".repeat(40);
+        let updates = parse_ref_updates(&format!(
+            "{zeros} {sha} refs/heads/ABC-1\n\
+             {sha} {sha} HEAD\n\
+             {sha} {zeros} refs/heads/old\n",
+            sha = "a".repeat(40),
+        ));
+
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates[0].reference, "refs/heads/ABC-1");
+        assert!(updates[0].is_creation());
+        assert!(!updates[1].is_creation() && !updates[2].is_creation());
+    }
+
+    #[test]
+    fn test_normalize_comment_string() {
+        assert_eq!(normalize_comment_string(";\n"), Some(String::from(";")));
+        assert_eq!(normalize_comment_string("//"), Some(String::from("//")));
+        assert_eq!(normalize_comment_string("auto"), Some(String::from("#")));
+        assert_eq!(normalize_comment_string(""), None);
+    }
+}
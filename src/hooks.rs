@@ -0,0 +1,193 @@
+//! Native git hooks running the binary
+//!
+//! Hook which already exists is renamed and called from the installed one
+//! before the binary, so it keeps working. Uninstall puts it back.
+
+use crate::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Line which marks hook scripts written by [`install`]
+const MARKER: &str = "# Installed by pyrust_task_id";
+
+/// Suffix of the hook which existed before installation
+const CHAINED_SUFFIX: &str = ".pre-pyrust-task-id";
+
+/// Return path of the hook which existed before installation
+///
+/// * `hook_path` - path of the installed hook
+fn chained_path(hook_path: &Path) -> PathBuf {
+    let mut path = hook_path.as_os_str().to_owned();
+    path.push(CHAINED_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// Return whether the file is a hook written by [`install`]
+///
+/// * `hook_path` - path of the hook
+fn is_installed(hook_path: &Path) -> bool {
+    fs::read_to_string(hook_path).is_ok_and(|content| {
+        content.lines().any(|line| line.starts_with(MARKER))
+    })
+}
+
+/// Quote the argument for POSIX shell
+///
+/// * `argument` - argument to quote
+fn quote(argument: &str) -> String {
+    format!("'{}'", argument.replace('\'', r"'\''"))
+}
+
+/// Return content of the hook script
+///
+/// * `command` - command the hook runs, hook arguments are appended to it
+fn hook_script(command: &[&str]) -> String {
+    let command: Vec<String> =
+        command.iter().map(|argument| quote(argument)).collect();
+
+    format!(
+        r#"#!/bin/sh
+{MARKER}, remove with `pyrust_task_id uninstall`
+chained="$0{CHAINED_SUFFIX}"
+if [ -x "$chained" ]; then
+    "$chained" "$@" || exit $?
+fi
+exec {} "$@"
+"#,
+        command.join(" ")
+    )
+}
+
+/// Make the file executable
+///
+/// * `path` - path of the file
+#[cfg(unix)]
+fn set_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+/// Make the file executable, hooks don't need it on this platform
+///
+/// * `path` - path of the file
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Write the hook running the command, existing hook is chained
+///
+/// Installing the hook again only updates the command.
+///
+/// * `hooks_dir` - directory git runs hooks from
+/// * `hook` - name of the hook, e.g. `commit-msg`
+/// * `command` - command the hook runs, hook arguments are appended to it
+pub(crate) fn install(
+    hooks_dir: &Path,
+    hook: &str,
+    command: &[&str],
+) -> Result<(), Error> {
+    let hook_path = hooks_dir.join(hook);
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |err| Error::Io(path, err)
+    };
+
+    fs::create_dir_all(hooks_dir).map_err(io_error(hooks_dir))?;
+    if hook_path.exists() && !is_installed(&hook_path) {
+        let chained_path = chained_path(&hook_path);
+        if chained_path.exists() {
+            return Err(Error::Io(
+                chained_path,
+                io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "hook is already chained, remove one of the hooks",
+                ),
+            ));
+        }
+        fs::rename(&hook_path, &chained_path).map_err(io_error(&hook_path))?;
+        log::info!(
+            "Existing hook is moved to `{}` and still runs first.",
+            chained_path.display()
+        );
+    }
+
+    fs::write(&hook_path, hook_script(command))
+        .map_err(io_error(&hook_path))?;
+    set_executable(&hook_path).map_err(io_error(&hook_path))?;
+    log::info!("Hook `{}` is installed.", hook_path.display());
+
+    Ok(())
+}
+
+/// Remove the hook written by [`install`] and restore the chained one
+///
+/// Return `false` if the hook isn't installed, other hooks are never removed.
+///
+/// * `hooks_dir` - directory git runs hooks from
+/// * `hook` - name of the hook, e.g. `commit-msg`
+pub(crate) fn uninstall(hooks_dir: &Path, hook: &str) -> Result<bool, Error> {
+    let hook_path = hooks_dir.join(hook);
+    if !is_installed(&hook_path) {
+        return Ok(false);
+    }
+
+    fs::remove_file(&hook_path)
+        .map_err(|err| Error::Io(hook_path.clone(), err))?;
+    let chained_path = chained_path(&hook_path);
+    if chained_path.exists() {
+        fs::rename(&chained_path, &hook_path)
+            .map_err(|err| Error::Io(chained_path, err))?;
+    }
+    log::info!("Hook `{}` is uninstalled.", hook_path.display());
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_install_and_uninstall() {
+        let hooks_dir = tempdir().unwrap();
+        let hook_path = hooks_dir.path().join("commit-msg");
+
+        install(hooks_dir.path(), "commit-msg", &["/bin/tool"]).unwrap();
+        let script = fs::read_to_string(&hook_path).unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("exec '/bin/tool' \"$@\""));
+
+        assert!(uninstall(hooks_dir.path(), "commit-msg").unwrap());
+        assert!(!hook_path.exists());
+        assert!(!uninstall(hooks_dir.path(), "commit-msg").unwrap());
+    }
+
+    #[test]
+    fn test_install_chains_existing_hook() {
+        let hooks_dir = tempdir().unwrap();
+        let hook_path = hooks_dir.path().join("commit-msg");
+        fs::write(&hook_path, "#!/bin/sh\nexit 0\n").unwrap();
+
+        install(hooks_dir.path(), "commit-msg", &["tool", "it's"]).unwrap();
+        // Second installation must not chain the installed hook
+        install(hooks_dir.path(), "commit-msg", &["tool", "it's"]).unwrap();
+        assert!(fs::read_to_string(&hook_path)
+            .unwrap()
+            .contains(r"exec 'tool' 'it'\''s' "));
+        assert_eq!(
+            fs::read_to_string(chained_path(&hook_path)).unwrap(),
+            "#!/bin/sh\nexit 0\n"
+        );
+
+        assert!(uninstall(hooks_dir.path(), "commit-msg").unwrap());
+        assert_eq!(
+            fs::read_to_string(&hook_path).unwrap(),
+            "#!/bin/sh\nexit 0\n"
+        );
+        assert!(!chained_path(&hook_path).exists());
+    }
+}
@@ -1,78 +1,93 @@
-use clap::Parser;
-use regex::Regex;
+//! Provide task id retrieved from branch name into commit message
+//!
+//! The crate is used as `commit-msg` hook binary, but the same logic is
+//! available as a library through [`TaskIdInjector`].
+
+mod cli;
+pub mod config;
+pub mod conventional;
+mod error;
+pub mod git;
+mod hooks;
+mod logger;
+pub mod normalize;
+#[cfg(feature = "python")]
+mod python;
+pub mod skip;
+pub mod template;
+mod trailers;
+
+pub use cli::parse_args_and_run;
+use config::{Config, FileConfig, OtherTaskId};
+use conventional::{ConventionalSubject, NonConventional};
+pub use error::{Error, TaskIDError};
+use normalize::{resolve_alias, Normalization};
+use regex::{Regex, RegexBuilder};
+use similar::TextDiff;
+use skip::{get_skip_reason, SkipRule};
 use std::collections::HashMap;
 use std::fs::{read_to_string, File};
 use std::io::Write;
-use std::process::{exit, Command};
-use strfmt::strfmt;
-
-#[derive(Parser)]
-struct Cli {
-    task_regex: String,
-    commit_message_template: String,
-    commit_message_file: String,
-}
+use std::path::{Path, PathBuf};
+use template::{slugify, Template};
+
+/// Commit message sources for which message is left untouched on
+/// `prepare-commit-msg` stage
+const SKIPPED_COMMIT_SOURCES: [&str; 2] = ["merge", "commit"];
 
+/// Task ids found in branch name
 #[derive(PartialEq, Debug)]
-enum TaskIDError {
-    NotInBranch,
-    WrongCapturingGroup,
+pub struct TaskMatch {
+    /// Unique task ids in order of appearance in branch name
+    pub task_ids: Vec<String>,
+    /// Index of the task regex that matched the branch name
+    pub rule: usize,
+    /// Named groups of the first match except `task_template`, groups which
+    /// didn't participate in the match are empty
+    pub groups: HashMap<String, String>,
 }
 
-/// Return current git branch name if git installed and the repo exists
-fn get_current_branch() -> String {
-    let mut command = Command::new("git");
-    command.args(["branch", "--show-current"]);
-
-    let output;
-    if let Ok(val) = command.output() {
-        output = val;
-    } else {
-        eprintln!("Make sure git is installed and git repo exists. Also make sure that stage for this hook is `commit-msg`.");
-        exit(1);
-    }
-
-    let output_text;
-    if let Ok(val) = String::from_utf8(output.stdout) {
-        output_text = val;
-    } else {
-        eprintln!("Got non utf-8 chars from git.");
-        exit(1);
-    }
-
-    String::from(output_text.trim())
+/// Result of providing task id into commit message
+#[derive(PartialEq, Debug)]
+pub enum Outcome {
+    /// Commit message was changed, contains the whole new message
+    Updated(String),
+    /// Commit message already contains task id
+    AlreadyContainsTaskId,
+    /// Task id can't be retrieved from branch name
+    NoTaskId(TaskIDError),
+    /// Commit message is intentionally left untouched, contains the reason
+    Skipped(String),
 }
 
-/// Return comment string which was set in git config
-fn get_git_comment_string() -> String {
-    let default_comment_char = String::from("#");
-    let git_config_options = ["core.commentString", "core.commentChar"];
-
-    let mut comment_string: String = default_comment_char;
-    for option in git_config_options {
-        let mut command = Command::new("git");
-        command.args(["config", "--get", option]);
-
-        let command_result = command
-            .output()
-            .expect("git must be installed in order to use this hook.");
-        let command_output = String::from_utf8(command_result.stdout)
-            .expect("Got non-utf8 text.");
-
-        if !command_output.is_empty() {
-            comment_string = command_output;
-            break;
-        }
-    }
+/// Result of checking commit which is already created
+#[derive(PartialEq, Debug)]
+pub enum CommitCheck {
+    /// Commit message contains task id
+    HasTaskId,
+    /// Commit is matched by skip rule, contains the reason
+    Skipped(String),
+    /// Commit message doesn't contain task id
+    MissingTaskId,
+}
 
-    comment_string
+/// Return regex of the cutline, content bellow it is ignored by git
+///
+/// * `comment_string` - comment string which was set in git config
+fn get_cutline_regex(comment_string: &str) -> Regex {
+    let comment_string = regex::escape(comment_string);
+    Regex::new(&format!("(^|\n)({comment_string} )?-+ ?>8 ?-+($|\n)")).unwrap()
 }
 
 /// Return commit message without comments and the content bellow cutline
 ///
 /// * `commit_message` - the message that will be used to get subject and body
-fn get_commit_message_without_comments(commit_message: &str) -> String {
-    let regex = Regex::new("(^|\n)-+>8-+($|\n)").unwrap();
+/// * `comment_string` - comment string which was set in git config
+fn get_commit_message_without_comments(
+    commit_message: &str,
+    comment_string: &str,
+) -> String {
+    let regex = get_cutline_regex(comment_string);
 
     let mut commit_message_without_cutline_section: &str = commit_message;
     if let Some(regex_match) = regex.find(commit_message) {
@@ -83,9 +98,11 @@ fn get_commit_message_without_comments(commit_message: &str) -> String {
     let mut result_message =
         commit_message_without_cutline_section.to_string();
 
-    let comment_string = get_git_comment_string();
-    let regex =
-        Regex::new(&format!(r"(^|\n){comment_string}.*($|\n)")).unwrap();
+    let regex = Regex::new(&format!(
+        r"(^|\n){}.*($|\n)",
+        regex::escape(comment_string)
+    ))
+    .unwrap();
 
     let matches = regex.find_iter(commit_message_without_cutline_section);
     for regex_match in matches {
@@ -96,12 +113,130 @@ fn get_commit_message_without_comments(commit_message: &str) -> String {
     result_message.trim().to_string()
 }
 
+/// Parts of the commit message file
+#[derive(PartialEq, Debug)]
+struct CommitMessageParts<'a> {
+    /// Comments before the first line written by user
+    leading: &'a str,
+    /// Lines written by user including comments between them
+    user_message: &'a str,
+    /// Comments and empty lines after the last line written by user and
+    /// everything starting from the cutline
+    trailing: &'a str,
+}
+
+impl CommitMessageParts<'_> {
+    /// Return commit message where the part written by user is replaced
+    ///
+    /// Comments and content bellow cutline are kept byte-for-byte. Comments
+    /// between lines written by user stay after the line they followed, or
+    /// after the line which replaced it.
+    ///
+    /// * `message` - message which should replace the part written by user
+    /// * `comment_string` - comment string which was set in git config
+    fn splice(&self, message: &str, comment_string: &str) -> String {
+        // `comments[i + 1]` follow `lines[i]`, `comments[0]` precede them
+        let mut lines = Vec::new();
+        let mut comments = vec![Vec::new()];
+        for line in self.user_message.split('\n') {
+            match comments.last_mut() {
+                Some(last) if line.starts_with(comment_string) => {
+                    last.push(line)
+                }
+                _ => {
+                    lines.push(line);
+                    comments.push(Vec::new());
+                }
+            }
+        }
+
+        let new_lines: Vec<&str> = message.split('\n').collect();
+        let mut result = comments[0].clone();
+        for op in TextDiff::from_slices(&lines, &new_lines).ops() {
+            let (_, old, new) = op.as_tag_tuple();
+            for (offset, line) in new_lines[new.clone()].iter().enumerate() {
+                result.push(line);
+                if offset < old.len() {
+                    result.extend(&comments[old.start + offset + 1]);
+                }
+            }
+            for index in old.skip(new.len()) {
+                result.extend(&comments[index + 1]);
+            }
+        }
+
+        let mut result = format!("{}{}", self.leading, result.join("\n"));
+        if !self.trailing.is_empty() {
+            result.push('\n');
+            result.push_str(self.trailing);
+        }
+
+        result
+    }
+}
+
+/// Split commit message into comments and the part written by user
+///
+/// * `commit_message` - the message to split
+/// * `comment_string` - comment string which was set in git config
+fn split_commit_message<'a>(
+    commit_message: &'a str,
+    comment_string: &str,
+) -> CommitMessageParts<'a> {
+    let is_comment_or_empty = |line: &str| {
+        line.trim().is_empty() || line.starts_with(comment_string)
+    };
+
+    let mut trailing_start = get_cutline_regex(comment_string)
+        .find(commit_message)
+        .map_or(commit_message.len(), |regex_match| {
+            // Keep new line which ends previous line in user message
+            regex_match.start()
+                + usize::from(regex_match.as_str().starts_with('\n'))
+        });
+
+    for line in commit_message[..trailing_start].split_inclusive('\n').rev() {
+        if !is_comment_or_empty(line) {
+            break;
+        }
+        trailing_start -= line.len();
+    }
+
+    let mut leading_end = 0;
+    if trailing_start > 0 {
+        for line in commit_message[..trailing_start].split_inclusive('\n') {
+            if !line.starts_with(comment_string) {
+                break;
+            }
+            leading_end += line.len();
+        }
+    }
+
+    CommitMessageParts {
+        leading: &commit_message[..leading_end],
+        user_message: &commit_message[leading_end..trailing_start],
+        trailing: &commit_message[trailing_start..],
+    }
+}
+
 /// Return commit message's subject and body retrieved from provided message
 ///
 /// * `commit_message` - the message that will be used to get subject and body
-fn get_subject_and_body(commit_message: &str) -> (String, String) {
-    let commit_message = get_commit_message_without_comments(commit_message);
+/// * `comment_string` - comment string which was set in git config
+fn get_subject_and_body(
+    commit_message: &str,
+    comment_string: &str,
+) -> (String, String) {
+    split_subject_and_body(&get_commit_message_without_comments(
+        commit_message,
+        comment_string,
+    ))
+}
 
+/// Split commit message without comments into subject and body
+///
+/// * `commit_message` - the message to split
+fn split_subject_and_body(commit_message: &str) -> (String, String) {
     if let Some((subject, body)) = commit_message.split_once("\n\n") {
         (subject.to_string(), body.to_string())
     } else {
@@ -109,24 +244,73 @@ fn get_subject_and_body(commit_message: &str) -> (String, String) {
     }
 }
 
-/// Return task id from current branch by the given regex
+/// Return task id from current branch by the given regexes
+///
+/// Regexes are tried in the given order and the first one that matches
+/// branch name and has `task_template` group wins. Every match of the winning
+/// regex is collected, so branch name may contain several task ids.
 ///
 /// * `branch_name` - name of the branch to retrieve task id from
-/// * `regex` - `regex` with task-id
+/// * `regexes` - regexes with task-id
 fn get_task_id(
     branch_name: &str,
-    regex: &Regex,
-) -> Result<String, TaskIDError> {
-    let regex_match = regex
-        .captures(branch_name)
-        .ok_or(TaskIDError::NotInBranch)?;
-
-    let captured_group = regex_match
-        .name("task_template")
-        .ok_or(TaskIDError::WrongCapturingGroup)?
-        .as_str();
-
-    Ok(String::from(captured_group))
+    regexes: &[Regex],
+) -> Result<TaskMatch, TaskIDError> {
+    let mut error = TaskIDError::NotInBranch;
+
+    for (rule, regex) in regexes.iter().enumerate() {
+        if !regex.is_match(branch_name) {
+            continue;
+        }
+
+        let mut task_ids: Vec<String> = Vec::new();
+        let mut groups: Option<HashMap<String, String>> = None;
+        for regex_match in regex.captures_iter(branch_name) {
+            let Some(captured_group) = regex_match.name("task_template")
+            else {
+                continue;
+            };
+
+            groups.get_or_insert_with(|| {
+                regex
+                    .capture_names()
+                    .flatten()
+                    .filter(|name| *name != "task_template")
+                    .map(|name| {
+                        let value = regex_match
+                            .name(name)
+                            .map_or("", |group| group.as_str());
+                        (name.to_string(), value.to_string())
+                    })
+                    .collect()
+            });
+
+            let task_id = captured_group.as_str();
+            if !task_ids.iter().any(|known| known == task_id) {
+                task_ids.push(task_id.to_string());
+            }
+        }
+
+        if task_ids.is_empty() {
+            log::debug!(
+                "Rule #{rule} `{regex}` has no `task_template` group."
+            );
+            error = TaskIDError::WrongCapturingGroup;
+            continue;
+        }
+
+        log::info!(
+            "Task ID `{}` found by rule #{rule} `{regex}`.",
+            task_ids.join("`, `")
+        );
+        return Ok(TaskMatch {
+            task_ids,
+            rule,
+            groups: groups.unwrap_or_default(),
+        });
+    }
+
+    Err(error)
 }
 
 /// Update last commit
@@ -135,114 +319,872 @@ fn get_task_id(
 ///
 /// * `filename` - name of the file to write new commit message to
 /// * `message` - message which should be provided to commit
-fn update_commit_with_message(filename: &str, message: &str) {
-    let mut commit_message_file =
-        File::create(filename).expect("Unable to open the file.");
+fn update_commit_with_message(
+    filename: &Path,
+    message: &str,
+) -> Result<(), Error> {
+    let io_error = |err| Error::Io(filename.to_path_buf(), err);
+    let mut commit_message_file = File::create(filename).map_err(io_error)?;
 
     commit_message_file
         .write_all(message.as_bytes())
-        .expect("Unable to write data");
+        .map_err(io_error)
 }
 
 /// Format commit message
 ///
+/// Named groups of task regex are available as placeholders too, but they
+/// can't override `subject`, `body` and `task_id`.
+///
 /// * `message_template` - template of the result message with placeholders
 /// * `commit_subject` - subject of the last made commit
 /// * `commit_body` - body of the last made commit
 /// * `task_id` - task id that should be provided into commit message
+/// * `groups` - named groups captured from branch name
 fn format_commit_message(
     message_template: &str,
     commit_subject: &str,
     commit_body: &str,
     task_id: &str,
-) -> String {
-    let mut placeholders = HashMap::new();
+    groups: &HashMap<String, String>,
+) -> Result<String, Error> {
+    let mut placeholders: HashMap<String, &str> = groups
+        .iter()
+        .map(|(name, value)| (name.clone(), value.as_str()))
+        .collect();
 
     placeholders.insert("subject".to_string(), commit_subject);
     placeholders.insert("body".to_string(), commit_body);
     placeholders.insert("task_id".to_string(), task_id);
 
-    if let Ok(updated_message) = strfmt(message_template, &placeholders) {
-        // Replace is needed in case when body is empty and there are some
-        // redundant empty lines
-        updated_message.replace("\n\n\n\n", "\n\n")
-    } else {
-        eprintln!("Message template is incorrect. It must contain `subject`, `body` and `task_id` placeholders.");
-        exit(1);
+    let template = Template::parse(message_template)?;
+    let updated_message = template.render(&placeholders)?;
+
+    // Templates without `{% if body %}` leave redundant empty lines in place
+    // of empty body, templates with conditions are rendered as written
+    if commit_body.is_empty() && !template.has_conditions() {
+        return Ok(updated_message.replace("\n\n\n\n", "\n\n"));
     }
+    Ok(updated_message)
+}
+
+/// Check that commit message contains task id
+///
+/// * `config` - config with regexes of task id
+/// * `commit_subject` - subject of the commit message
+/// * `commit_body` - body of the commit message
+fn message_has_task_id(
+    config: &Config,
+    commit_subject: &str,
+    commit_body: &str,
+) -> Result<bool, Error> {
+    Ok(config.task_id_regexes()?.iter().any(|regex| {
+        !find_whole_words(regex, commit_subject).is_empty()
+            || !find_whole_words(regex, commit_body).is_empty()
+    }))
 }
 
-/// Run `pyrust_task_id`
-fn provide_task_id_into_commit(
-    task_regex_raw: &str,
-    commit_message_template: &str,
-    commit_message_filename: &str,
+/// Return every match of the regex which is not a part of a longer word
+///
+/// * `regex` - regex to search with
+/// * `text` - text to search in
+fn find_whole_words<'a>(regex: &Regex, text: &'a str) -> Vec<&'a str> {
+    let is_word_char = |char: char| char.is_alphanumeric() || char == '_';
+
+    regex
+        .find_iter(text)
+        .filter(|regex_match| {
+            !text[..regex_match.start()]
+                .chars()
+                .next_back()
+                .is_some_and(is_word_char)
+                && !text[regex_match.end()..]
+                    .chars()
+                    .next()
+                    .is_some_and(is_word_char)
+        })
+        .map(|regex_match| regex_match.as_str())
+        .collect()
+}
+
+/// Check that text contains task id as a whole word
+///
+/// `ABC-1` is not found in text mentioning only `ABC-12`.
+///
+/// * `text` - text to search in
+/// * `task_id` - task id to search for
+/// * `ignore_case` - whether `abc-1` should be found as `ABC-1`
+fn contains_task_id(text: &str, task_id: &str, ignore_case: bool) -> bool {
+    let regex = RegexBuilder::new(&regex::escape(task_id))
+        .case_insensitive(ignore_case)
+        .build()
+        .expect("Escaped task id is valid.");
+
+    !find_whole_words(&regex, text).is_empty()
+}
+
+/// Check that task id is either in branch name or in commit message
+///
+/// Error with explanation is returned if neither branch name nor commit
+/// message contain task id and the branch is not exempt.
+///
+/// * `config` - config with task regexes and exempt branches
+/// * `branch_name` - name of the current branch
+/// * `commit_subject` - subject of the commit message
+/// * `commit_body` - body of the commit message
+fn check_task_id(
+    config: &Config,
     branch_name: &str,
-) {
-    // Remove escaping for commit message template
-    let template = commit_message_template.replace("\\n", "\n");
+    commit_subject: &str,
+    commit_body: &str,
+) -> Result<(), Error> {
+    if get_task_id(branch_name, &config.task_regexes).is_ok() {
+        return Ok(());
+    }
 
-    let task_regex;
-    if let Ok(val) = Regex::new(task_regex_raw) {
-        task_regex = val;
-    } else {
-        eprintln!("Make sure task regex is correct.");
-        exit(1);
+    if config.is_exempt_branch(branch_name) {
+        log::info!("Branch `{branch_name}` doesn't require task id.");
+        return Ok(());
     }
 
-    let commit_message =
-        read_to_string(commit_message_filename).unwrap_or_default();
-    let commit_message = commit_message.trim();
+    if message_has_task_id(config, commit_subject, commit_body)? {
+        log::info!("Task ID is found in commit message.");
+        return Ok(());
+    }
 
-    let (commit_subject, commit_body) = get_subject_and_body(commit_message);
+    Err(Error::MissingTaskId(format!(
+        "Task ID is required, but branch `{branch_name}` doesn't match any of {} and commit message doesn't contain task id matching any of {}. Branches that don't require task id: {}.",
+        join_quoted(config.task_regexes.iter().map(Regex::as_str)),
+        join_quoted(config.task_id_regexes()?.iter().map(Regex::as_str)),
+        join_quoted(config.exempt_branches.iter().map(String::as_str)),
+    )))
+}
 
-    let task_id;
-    match get_task_id(branch_name, &task_regex) {
-        Ok(val) => task_id = val,
-        Err(err) => match err {
-            TaskIDError::WrongCapturingGroup => {
-                log::warn!("Make sure you included capturing group with name `task_template`.");
-                return;
-            }
-            // We don't want to raise error because if can't get task id from
-            // branch name, it means it may be `develop` or `main` branch
-            TaskIDError::NotInBranch => return,
-        },
+/// Return values quoted with backticks and separated by commas
+///
+/// * `values` - values to join
+fn join_quoted<'a>(values: impl Iterator<Item = &'a str>) -> String {
+    format!("`{}`", values.collect::<Vec<_>>().join("`, `"))
+}
+
+/// Read the whole file
+///
+/// * `path` - path to the file
+fn read_file(path: &Path) -> Result<String, Error> {
+    read_to_string(path).map_err(|err| Error::Io(path.to_path_buf(), err))
+}
+
+/// Builder of [`TaskIdInjector`]
+///
+/// Options which are not set get the same defaults as in config file.
+#[derive(Default, Debug)]
+pub struct TaskIdInjectorBuilder {
+    raw: FileConfig,
+    comment_string: Option<String>,
+    git_dir: Option<PathBuf>,
+}
+
+impl TaskIdInjectorBuilder {
+    /// Add regex with `task_template` named capturing group
+    ///
+    /// Regexes are tried in the order they were added.
+    pub fn task_regex(mut self, task_regex: impl Into<String>) -> Self {
+        self.raw
+            .task_regex
+            .get_or_insert_with(Vec::new)
+            .push(task_regex.into());
+        self
     }
 
-    if commit_subject.contains(&task_id) || commit_body.contains(&task_id) {
-        return;
+    /// Set template of the commit message
+    pub fn template(mut self, template: impl Into<String>) -> Self {
+        self.raw.template = Some(template.into());
+        self
     }
 
-    let updated_commit_message = format_commit_message(
-        &template,
-        &commit_subject,
-        &commit_body,
-        &task_id,
-    );
+    /// Insert every task id found in branch name, not only the first one
+    pub fn all_task_ids(mut self, all_task_ids: bool) -> Self {
+        self.raw.all_task_ids = Some(all_task_ids);
+        self
+    }
+
+    /// Set string used to join several task ids
+    pub fn task_id_separator(mut self, separator: impl Into<String>) -> Self {
+        self.raw.task_id_separator = Some(separator.into());
+        self
+    }
 
-    update_commit_with_message(
-        commit_message_filename,
-        &updated_commit_message,
-    );
+    /// Fail if task id is neither in branch name nor in commit message
+    pub fn require(mut self, require: bool) -> Self {
+        self.raw.require = Some(require);
+        self
+    }
+
+    /// Add regex of task id used to find task id in commit message
+    pub fn task_id_regex(mut self, task_id_regex: impl Into<String>) -> Self {
+        self.raw
+            .task_id_regex
+            .get_or_insert_with(Vec::new)
+            .push(task_id_regex.into());
+        self
+    }
+
+    /// Add branch that doesn't require task id, replaces default ones
+    pub fn exempt_branch(mut self, branch: impl Into<String>) -> Self {
+        self.raw
+            .exempt_branches
+            .get_or_insert_with(Vec::new)
+            .push(branch.into());
+        self
+    }
+
+    /// Insert task id as trailer with the given key instead of using template
+    pub fn trailer(mut self, key: impl Into<String>) -> Self {
+        self.raw.trailer = Some(key.into());
+        self
+    }
+
+    /// Insert task id into Conventional Commits subject instead of using
+    /// template
+    pub fn conventional(mut self, placement: conventional::Placement) -> Self {
+        self.raw.conventional = Some(placement);
+        self
+    }
+
+    /// Set what to do with subject which doesn't follow Conventional Commits
+    pub fn non_conventional(mut self, policy: NonConventional) -> Self {
+        self.raw.non_conventional = Some(policy);
+        self
+    }
+
+    /// Find task id in commit message ignoring case
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.raw.ignore_case = Some(ignore_case);
+        self
+    }
+
+    /// Set what to do if commit message contains task id other than the
+    /// one from branch name
+    pub fn other_task_id(mut self, policy: OtherTaskId) -> Self {
+        self.raw.other_task_id = Some(policy);
+        self
+    }
+
+    /// Add kind of commits left untouched, replaces default ones
+    pub fn skip_rule(mut self, rule: SkipRule) -> Self {
+        self.raw.skip.get_or_insert_with(Vec::new).push(rule);
+        self
+    }
+
+    /// Set rules turning task id into its canonical form
+    pub fn normalization(mut self, normalization: Normalization) -> Self {
+        self.raw.normalize = Some(normalization);
+        self
+    }
+
+    /// Add project key alias, task ids with it get the canonical key
+    pub fn alias(
+        mut self,
+        alias: impl Into<String>,
+        canonical_key: impl Into<String>,
+    ) -> Self {
+        self.raw
+            .aliases
+            .get_or_insert_with(HashMap::new)
+            .insert(alias.into(), canonical_key.into());
+        self
+    }
+
+    /// Pre-fill empty commit message on `prepare-commit-msg` stage
+    pub fn prefill_empty(mut self, prefill_empty: bool) -> Self {
+        self.raw.prefill_empty = Some(prefill_empty);
+        self
+    }
+
+    /// Set template of the branch name created from task id and title
+    pub fn branch_template(mut self, template: impl Into<String>) -> Self {
+        self.raw.branch_template = Some(template.into());
+        self
+    }
+
+    /// Set git dir used to check merge and cherry-pick in progress
+    ///
+    /// Without it `merge-head` and `cherry-pick` skip rules are not checked.
+    pub fn git_dir(mut self, git_dir: impl Into<PathBuf>) -> Self {
+        self.git_dir = Some(git_dir.into());
+        self
+    }
+
+    /// Set comment string instead of reading it from git config
+    pub fn comment_string(
+        mut self,
+        comment_string: impl Into<String>,
+    ) -> Self {
+        self.comment_string = Some(comment_string.into());
+        self
+    }
+
+    /// Validate options and build the injector
+    pub fn build(self) -> Result<TaskIdInjector, Error> {
+        Ok(TaskIdInjector {
+            config: Config::try_from(self.raw)?,
+            comment_string: self.comment_string,
+            git_dir: self.git_dir,
+        })
+    }
 }
 
-/// Prase args and run
-pub fn parse_args_and_run() {
-    let args = Cli::parse();
-    let branch_name = get_current_branch();
-
-    provide_task_id_into_commit(
-        &args.task_regex,
-        &args.commit_message_template,
-        &args.commit_message_file,
-        &branch_name,
-    );
+/// Provides task id retrieved from branch name into commit message
+#[derive(Debug)]
+pub struct TaskIdInjector {
+    config: Config,
+    comment_string: Option<String>,
+    git_dir: Option<PathBuf>,
+}
+
+impl From<Config> for TaskIdInjector {
+    fn from(config: Config) -> Self {
+        TaskIdInjector {
+            config,
+            comment_string: None,
+            git_dir: None,
+        }
+    }
+}
+
+impl TaskIdInjector {
+    /// Return builder of the injector
+    pub fn builder() -> TaskIdInjectorBuilder {
+        TaskIdInjectorBuilder::default()
+    }
+
+    /// Return validated config of the injector
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Return comment string either set explicitly or taken from git config
+    fn comment_string(&self) -> Result<String, Error> {
+        match &self.comment_string {
+            Some(comment_string) => Ok(comment_string.clone()),
+            None => git::get_git_comment_string(),
+        }
+    }
+
+    /// Return reason to leave commit message untouched by skip rules
+    ///
+    /// State files are checked only if git dir is set explicitly.
+    ///
+    /// * `commit_subject` - subject of the commit message
+    fn skip_reason(&self, commit_subject: &str) -> Option<String> {
+        get_skip_reason(
+            &self.config.skip_rules,
+            commit_subject,
+            self.git_dir.as_deref(),
+        )
+    }
+
+    /// Return reason to leave existing commit untouched by skip rules
+    ///
+    /// State files are not checked, they describe the current operation and
+    /// not the commit.
+    ///
+    /// * `commit_subject` - subject of the commit message
+    /// * `parents` - number of parents of the commit
+    fn commit_skip_reason(
+        &self,
+        commit_subject: &str,
+        parents: usize,
+    ) -> Option<String> {
+        if parents > 1 && self.config.skip_rules.contains(&SkipRule::Merge) {
+            return Some(String::from(
+                "Merge commit is left untouched by `merge` rule.",
+            ));
+        }
+
+        get_skip_reason(&self.config.skip_rules, commit_subject, None)
+    }
+
+    /// Return task ids and named groups retrieved from branch name
+    ///
+    /// Only the first task id is returned unless `all_task_ids` is set.
+    ///
+    /// * `branch_name` - name of the branch to retrieve task ids from
+    pub fn task_match(&self, branch_name: &str) -> Result<TaskMatch, Error> {
+        let mut task_match =
+            get_task_id(branch_name, &self.config.task_regexes)?;
+        let mut task_ids: Vec<String> = Vec::new();
+        for task_id in &task_match.task_ids {
+            let task_id = self.canonical_task_id(task_id);
+            if !task_ids.contains(&task_id) {
+                task_ids.push(task_id);
+            }
+        }
+        task_match.task_ids = task_ids;
+        if !self.config.all_task_ids {
+            task_match.task_ids.truncate(1);
+        }
+
+        Ok(task_match)
+    }
+
+    /// Return task ids retrieved from branch name
+    ///
+    /// * `branch_name` - name of the branch to retrieve task ids from
+    pub fn task_ids(&self, branch_name: &str) -> Result<Vec<String>, Error> {
+        Ok(self.task_match(branch_name)?.task_ids)
+    }
+
+    /// Return task id normalized and with project key alias resolved
+    ///
+    /// * `task_id` - task id as it is written
+    fn canonical_task_id(&self, task_id: &str) -> String {
+        resolve_alias(
+            &self.config.normalization.apply(task_id),
+            &self.config.aliases,
+        )
+    }
+
+    /// Check that text contains another spelling of the task id, which is
+    /// normalized into it
+    ///
+    /// Nothing is found if task id regexes would match unrelated text.
+    ///
+    /// * `text` - text to search in
+    /// * `task_id` - normalized task id
+    fn mentions_in_other_spelling(&self, text: &str, task_id: &str) -> bool {
+        let Ok(task_id_regexes) = self.config.task_id_regexes() else {
+            return false;
+        };
+
+        task_id_regexes.iter().any(|regex| {
+            find_whole_words(regex, text)
+                .into_iter()
+                .any(|found| self.canonical_task_id(found) == task_id)
+        })
+    }
+
+    /// Return branch name for the task rendered from branch template
+    ///
+    /// Task regex must retrieve the same task id from the result, so commits
+    /// on the branch always get it.
+    ///
+    /// * `task_id` - id of the task
+    /// * `title` - title of the task, it is slugified for `slug` placeholder
+    pub fn branch_name(
+        &self,
+        task_id: &str,
+        title: &str,
+    ) -> Result<String, Error> {
+        let slug = slugify(title);
+        let values = HashMap::from([
+            (String::from("task_id"), task_id),
+            (String::from("slug"), slug.as_str()),
+            (String::from("title"), title),
+        ]);
+        let branch_name =
+            Template::parse(&self.config.branch_template)?.render(&values)?;
+
+        match get_task_id(&branch_name, &self.config.task_regexes) {
+            Ok(task_match)
+                if self.canonical_task_id(&task_match.task_ids[0])
+                    == self.canonical_task_id(task_id) =>
+            {
+                Ok(branch_name)
+            }
+            Err(TaskIDError::WrongCapturingGroup) => {
+                Err(TaskIDError::WrongCapturingGroup.into())
+            }
+            _ => Err(Error::MissingTaskId(format!(
+                "Task regex doesn't retrieve task id `{task_id}` from branch `{branch_name}`, make sure branch template `{}` agrees with any of {}.",
+                self.config.branch_template,
+                join_quoted(self.config.task_regexes.iter().map(Regex::as_str)),
+            ))),
+        }
+    }
+
+    /// Return commit message with task id provided into it
+    ///
+    /// Only the part written by user is changed, comments and content bellow
+    /// cutline are kept unchanged. Empty message is left untouched, so git
+    /// aborts the commit.
+    ///
+    /// * `commit_message` - the whole current commit message
+    /// * `branch_name` - name of the branch to retrieve task id from
+    pub fn update_message(
+        &self,
+        commit_message: &str,
+        branch_name: &str,
+    ) -> Result<Outcome, Error> {
+        self.provide_task_id_into_message(commit_message, branch_name, false)
+    }
+
+    /// Return commit message with task id provided into the part written
+    /// by user
+    ///
+    /// * `commit_message` - the whole current commit message
+    /// * `branch_name` - name of the branch to retrieve task id from
+    /// * `fill_empty` - whether message without lines written by user gets
+    ///   task id too
+    fn provide_task_id_into_message(
+        &self,
+        commit_message: &str,
+        branch_name: &str,
+        fill_empty: bool,
+    ) -> Result<Outcome, Error> {
+        let comment_string = self.comment_string()?;
+        let parts = split_commit_message(commit_message, &comment_string);
+        if !fill_empty && parts.user_message.trim().is_empty() {
+            return Ok(Outcome::Skipped(String::from(
+                "Empty commit message is left untouched, so the commit is aborted.",
+            )));
+        }
+
+        let (commit_subject, commit_body) =
+            get_subject_and_body(parts.user_message.trim(), &comment_string);
+
+        if let Some(reason) = self.skip_reason(&commit_subject) {
+            return Ok(Outcome::Skipped(reason));
+        }
+
+        Ok(
+            match self.provide_task_id(
+                commit_subject,
+                &commit_body,
+                branch_name,
+            )? {
+                Outcome::Updated(message) => {
+                    Outcome::Updated(parts.splice(&message, &comment_string))
+                }
+                outcome => outcome,
+            },
+        )
+    }
+
+    /// Return message of existing commit with task id provided into it
+    ///
+    /// The message is taken as it is stored in git, so lines starting with
+    /// comment string are a part of it and nothing is stripped.
+    ///
+    /// * `commit_message` - message of the commit
+    /// * `parents` - number of parents of the commit
+    /// * `branch_name` - name of the branch to retrieve task id from
+    pub fn update_commit(
+        &self,
+        commit_message: &str,
+        parents: usize,
+        branch_name: &str,
+    ) -> Result<Outcome, Error> {
+        let (commit_subject, commit_body) =
+            split_subject_and_body(commit_message.trim());
+
+        if let Some(reason) = self.commit_skip_reason(&commit_subject, parents)
+        {
+            return Ok(Outcome::Skipped(reason));
+        }
+
+        self.provide_task_id(commit_subject, &commit_body, branch_name)
+    }
+
+    /// Return subject and body with task id provided into them
+    ///
+    /// The updated message is returned without comments, skip rules are
+    /// expected to be checked already.
+    ///
+    /// * `commit_subject` - subject of the commit message
+    /// * `commit_body` - body of the commit message
+    /// * `branch_name` - name of the branch to retrieve task id from
+    fn provide_task_id(
+        &self,
+        commit_subject: String,
+        commit_body: &str,
+        branch_name: &str,
+    ) -> Result<Outcome, Error> {
+        let TaskMatch {
+            mut task_ids,
+            groups,
+            ..
+        } = match self.task_match(branch_name) {
+            Ok(val) => val,
+            Err(Error::TaskId(err)) => {
+                if err == TaskIDError::WrongCapturingGroup {
+                    log::warn!("{err}");
+                }
+                // We don't want to raise error because if can't get task id
+                // from branch name, it means it may be `develop` or `main`
+                // branch
+                return Ok(Outcome::NoTaskId(err));
+            }
+            Err(err) => return Err(err),
+        };
+
+        let ignore_case = self.config.ignore_case;
+        let message = format!("{commit_subject}\n\n{commit_body}");
+        let branch_task_ids = task_ids.clone();
+        task_ids.retain(|task_id| {
+            !contains_task_id(&message, task_id, ignore_case)
+                && !self.mentions_in_other_spelling(&message, task_id)
+        });
+        if task_ids.is_empty() {
+            return Ok(Outcome::AlreadyContainsTaskId);
+        }
+
+        if self.config.other_task_id != OtherTaskId::Add {
+            let mut other_task_ids: Vec<&str> = Vec::new();
+            for regex in self.config.task_id_regexes()? {
+                for found in find_whole_words(regex, &message) {
+                    let normalized = self.canonical_task_id(found);
+                    let is_known = branch_task_ids.iter().any(|task_id| {
+                        task_id == found
+                            || *task_id == normalized
+                            || ignore_case
+                                && task_id.eq_ignore_ascii_case(found)
+                    });
+                    if !is_known && !other_task_ids.contains(&found) {
+                        other_task_ids.push(found);
+                    }
+                }
+            }
+
+            if !other_task_ids.is_empty() {
+                let other_task_ids = other_task_ids.join("`, `");
+                if self.config.other_task_id == OtherTaskId::Skip {
+                    return Ok(Outcome::Skipped(format!(
+                        "Commit message already references `{other_task_ids}`."
+                    )));
+                }
+                return Err(Error::OtherTaskId(format!(
+                    "Commit message references `{other_task_ids}`, but task id of branch `{branch_name}` is `{}`.",
+                    branch_task_ids.join("`, `")
+                )));
+            }
+        }
+        let updated_commit_message = if let Some(placement) =
+            self.config.conventional
+        {
+            let Some(subject) = ConventionalSubject::parse(&commit_subject)
+            else {
+                return match self.config.non_conventional {
+                    NonConventional::Skip => Ok(Outcome::Skipped(format!(
+                        "Subject `{commit_subject}` doesn't follow Conventional Commits."
+                    ))),
+                    NonConventional::Fail => {
+                        Err(Error::NotConventional(commit_subject))
+                    }
+                };
+            };
+            let subject = subject.with_task_ids(placement, &task_ids);
+
+            format!("{subject}\n\n{commit_body}").trim_end().to_string()
+        } else if let Some(key) = &self.config.trailer {
+            trailers::add_trailers(
+                &format!("{commit_subject}\n\n{commit_body}"),
+                key,
+                &task_ids,
+            )
+        } else {
+            format_commit_message(
+                &self.config.template,
+                &commit_subject,
+                commit_body,
+                &task_ids.join(&self.config.task_id_separator),
+                &groups,
+            )?
+        };
+
+        Ok(Outcome::Updated(updated_commit_message))
+    }
+
+    /// Check that task id is either in branch name or in commit message
+    ///
+    /// [`Error::MissingTaskId`] is returned if task id is missing and the
+    /// branch is not exempt.
+    ///
+    /// * `commit_message` - the whole current commit message
+    /// * `branch_name` - name of the current branch
+    pub fn check_message(
+        &self,
+        commit_message: &str,
+        branch_name: &str,
+    ) -> Result<(), Error> {
+        let (commit_subject, commit_body) = get_subject_and_body(
+            commit_message.trim(),
+            &self.comment_string()?,
+        );
+
+        if let Some(reason) = self.skip_reason(&commit_subject) {
+            log::info!("{reason}");
+            return Ok(());
+        }
+
+        check_task_id(&self.config, branch_name, &commit_subject, &commit_body)
+    }
+
+    /// Check that branch name contains task id
+    ///
+    /// [`Error::MissingTaskId`] with the expected shape of the name is
+    /// returned unless the branch matches any task regex or is exempt.
+    ///
+    /// * `branch_name` - name of the branch
+    pub fn check_branch(&self, branch_name: &str) -> Result<(), Error> {
+        match get_task_id(branch_name, &self.config.task_regexes) {
+            Ok(_) => return Ok(()),
+            Err(TaskIDError::WrongCapturingGroup) => {
+                return Err(TaskIDError::WrongCapturingGroup.into())
+            }
+            Err(TaskIDError::NotInBranch) => {}
+        }
+
+        if self.config.is_exempt_branch(branch_name) {
+            log::info!("Branch `{branch_name}` doesn't require task id.");
+            return Ok(());
+        }
+
+        Err(Error::MissingTaskId(format!(
+            "Branch `{branch_name}` doesn't contain task id, its name must match any of {}. Branches that don't require task id: {}.",
+            join_quoted(self.config.task_regexes.iter().map(Regex::as_str)),
+            join_quoted(self.config.exempt_branches.iter().map(String::as_str)),
+        )))
+    }
+
+    /// Check that task id is either in branch name or in commit message file
+    ///
+    /// * `path` - file with commit message
+    /// * `branch_name` - name of the current branch
+    pub fn check_file(
+        &self,
+        path: impl AsRef<Path>,
+        branch_name: &str,
+    ) -> Result<(), Error> {
+        self.check_message(&read_file(path.as_ref())?, branch_name)
+    }
+
+    /// Check that commit which is already created contains task id
+    ///
+    /// Branch name is not taken into account, only the commit message. The
+    /// message is searched as it is stored in git, comments are not
+    /// stripped.
+    ///
+    /// * `commit_message` - message of the commit
+    /// * `parents` - number of parents of the commit
+    pub fn check_commit(
+        &self,
+        commit_message: &str,
+        parents: usize,
+    ) -> Result<CommitCheck, Error> {
+        let (commit_subject, commit_body) =
+            split_subject_and_body(commit_message.trim());
+
+        if let Some(reason) = self.commit_skip_reason(&commit_subject, parents)
+        {
+            return Ok(CommitCheck::Skipped(reason));
+        }
+
+        if message_has_task_id(&self.config, &commit_subject, &commit_body)? {
+            Ok(CommitCheck::HasTaskId)
+        } else {
+            Ok(CommitCheck::MissingTaskId)
+        }
+    }
+
+    /// Return commit message the same way [`Self::update_file`] would
+    /// write it
+    ///
+    /// If task id is required, the message is checked first.
+    ///
+    /// * `commit_message` - the whole current commit message
+    /// * `branch_name` - name of the current branch
+    pub fn commit_message(
+        &self,
+        commit_message: &str,
+        branch_name: &str,
+    ) -> Result<Outcome, Error> {
+        if self.config.require {
+            self.check_message(commit_message, branch_name)?;
+        }
+
+        self.update_message(commit_message, branch_name)
+    }
+
+    /// Return commit message the same way [`Self::prepare_file`] would
+    /// write it
+    ///
+    /// Message without lines written by user is left untouched unless
+    /// `prefill_empty` is set, so closing the editor still aborts the commit.
+    ///
+    /// * `commit_message` - the whole current commit message
+    /// * `branch_name` - name of the current branch
+    /// * `commit_source` - source of the commit message passed by git
+    pub fn prepare_message(
+        &self,
+        commit_message: &str,
+        branch_name: &str,
+        commit_source: Option<&str>,
+    ) -> Result<Outcome, Error> {
+        if let Some(commit_source) = commit_source.filter(|commit_source| {
+            SKIPPED_COMMIT_SOURCES.contains(commit_source)
+        }) {
+            return Ok(Outcome::Skipped(format!(
+                "Commit message from `{commit_source}` is left untouched."
+            )));
+        }
+
+        self.provide_task_id_into_message(
+            commit_message,
+            branch_name,
+            self.config.prefill_empty,
+        )
+    }
+
+    /// Provide task id into commit message file, use on `commit-msg` stage
+    ///
+    /// If task id is required, the file is checked first.
+    ///
+    /// * `path` - file with commit message
+    /// * `branch_name` - name of the current branch
+    pub fn update_file(
+        &self,
+        path: impl AsRef<Path>,
+        branch_name: &str,
+    ) -> Result<Outcome, Error> {
+        let path = path.as_ref();
+        let outcome = self.commit_message(&read_file(path)?, branch_name)?;
+        if let Outcome::Updated(updated_commit_message) = &outcome {
+            update_commit_with_message(path, updated_commit_message)?;
+        }
+
+        Ok(outcome)
+    }
+
+    /// Pre-fill commit message file, use on `prepare-commit-msg` stage
+    ///
+    /// * `path` - file with commit message
+    /// * `branch_name` - name of the current branch
+    /// * `commit_source` - source of the commit message passed by git
+    pub fn prepare_file(
+        &self,
+        path: impl AsRef<Path>,
+        branch_name: &str,
+        commit_source: Option<&str>,
+    ) -> Result<Outcome, Error> {
+        let path = path.as_ref();
+        let outcome = self.prepare_message(
+            &read_file(path)?,
+            branch_name,
+            commit_source,
+        )?;
+        if let Outcome::Updated(updated_commit_message) = &outcome {
+            update_commit_with_message(path, updated_commit_message)?;
+        }
+
+        Ok(outcome)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use config::ConfigError;
     use tempfile::NamedTempFile;
 
     #[test]
@@ -252,9 +1194,88 @@ mod tests {
 
         let regex =
             Regex::new(r"feature/(?P<task_template>ABC-\d+).*").unwrap();
-        let task_id = get_task_id(branch_name, &regex).unwrap();
+        let task_ids = get_task_id(branch_name, &[regex]).unwrap().task_ids;
+
+        assert_eq!(task_ids, [expected]);
+    }
 
-        assert_eq!(task_id, expected);
+    #[test]
+    fn test_get_task_id_first_matching_regex_wins() {
+        let branch_name = "bugfix/XYZ-9_fix-login";
+        let regexes = [
+            Regex::new(r"feature/(?P<task_template>ABC-\d+)-.*").unwrap(),
+            Regex::new(r"bugfix/(?P<task_template>XYZ-\d+)_.*").unwrap(),
+            Regex::new(r"(?P<task_template>[A-Z]+-\d+)").unwrap(),
+        ];
+        let expected = TaskMatch {
+            task_ids: vec![String::from("XYZ-9")],
+            rule: 1,
+            groups: HashMap::new(),
+        };
+
+        assert_eq!(get_task_id(branch_name, &regexes), Ok(expected));
+    }
+
+    #[test]
+    fn test_get_task_id_skips_regex_without_named_capturing_group() {
+        let branch_name = "gh-42-fix";
+        let regexes = [
+            Regex::new(r"gh-(\d+)").unwrap(),
+            Regex::new(r"(?P<task_template>gh-\d+)").unwrap(),
+        ];
+
+        let task_ids = get_task_id(branch_name, &regexes).unwrap().task_ids;
+
+        assert_eq!(task_ids, ["gh-42"]);
+    }
+
+    #[test]
+    fn test_get_task_id_collects_unique_task_ids() {
+        let branch_name = "feature/ABC-12-ABC-13-ABC-12-shared-fix";
+        let regex = Regex::new(r"(?P<task_template>ABC-\d+)").unwrap();
+
+        let task_ids = get_task_id(branch_name, &[regex]).unwrap().task_ids;
+
+        assert_eq!(task_ids, ["ABC-12", "ABC-13"]);
+    }
+
+    #[test]
+    fn test_get_task_id_collects_named_groups() {
+        let branch_name = "feat/ABC-12-shared-fix";
+        let regex = Regex::new(
+            r"(?P<type>feat|fix)/(?P<task_template>(?P<project>[A-Z]+)-\d+)(-(?P<slug>.*))?(?P<suffix>_v\d+)?",
+        )
+        .unwrap();
+
+        let groups = get_task_id(branch_name, &[regex]).unwrap().groups;
+
+        assert_eq!(
+            groups,
+            HashMap::from([
+                (String::from("type"), String::from("feat")),
+                (String::from("project"), String::from("ABC")),
+                (String::from("slug"), String::from("shared-fix")),
+                (String::from("suffix"), String::new()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_check_task_id() {
+        let config = Config::new(
+            &[r"feature/(?P<task_template>ABC-\d+).*"],
+            "{subject}\n\n{task_id}",
+        )
+        .unwrap();
+
+        assert!(
+            check_task_id(&config, "feature/ABC-1-x", "Subject", "").is_ok()
+        );
+        assert!(check_task_id(&config, "release/1.0", "Subject", "").is_ok());
+        assert!(
+            check_task_id(&config, "hotfix-foo", "Subject", "ABC-2").is_ok()
+        );
+        assert!(check_task_id(&config, "hotfix-foo", "Subject", "").is_err());
     }
 
     #[test]
@@ -263,7 +1284,7 @@ mod tests {
         let expected_body = "Commit body";
 
         let (subject, body) =
-            get_subject_and_body("Commit subject\n\nCommit body");
+            get_subject_and_body("Commit subject\n\nCommit body", "#");
 
         assert_eq!(subject, expected_subject);
         assert_eq!(body, expected_body);
@@ -274,7 +1295,7 @@ mod tests {
         let expected_subject = "Commit subject";
         let expected_body = "";
 
-        let (subject, body) = get_subject_and_body("Commit subject");
+        let (subject, body) = get_subject_and_body("Commit subject", "#");
 
         assert_eq!(subject, expected_subject);
         assert_eq!(body, expected_body);
@@ -288,8 +1309,7 @@ mod tests {
 
         let (subject, body) =
             get_subject_and_body(
-                "Commit subject\n\nCommit body\nAnother line\n\nEmpty line commit body"
-            );
+                "Commit subject\n\nCommit body\nAnother line\n\nEmpty line commit body", "#");
 
         assert_eq!(subject, expected_subject);
         assert_eq!(body, expected_body);
@@ -302,7 +1322,7 @@ mod tests {
 
         let regex = Regex::new(r"feature/(ABC-\d+).*").unwrap();
 
-        assert_eq!(get_task_id(branch_name, &regex), expected);
+        assert_eq!(get_task_id(branch_name, &[regex]), expected);
     }
 
     #[test]
@@ -313,7 +1333,7 @@ mod tests {
         let regex =
             Regex::new(r"feature/(?P<task_template>ABC-\d+).*").unwrap();
 
-        assert_eq!(get_task_id(branch_name, &regex), expected);
+        assert_eq!(get_task_id(branch_name, &[regex]), expected);
     }
 
     #[test]
@@ -327,8 +1347,14 @@ mod tests {
             "Test commit subject\n\nTest commit body\n\nTEST-111",
         );
 
-        let formatted_message =
-            format_commit_message(template, subject, body, task_id);
+        let formatted_message = format_commit_message(
+            template,
+            subject,
+            body,
+            task_id,
+            &HashMap::new(),
+        )
+        .unwrap();
 
         assert_eq!(formatted_message, expected);
     }
@@ -342,12 +1368,49 @@ mod tests {
 
         let expected = String::from("Test commit subject\n\nTEST-111");
 
-        let formatted_message =
-            format_commit_message(template, subject, body, task_id);
+        let formatted_message = format_commit_message(
+            template,
+            subject,
+            body,
+            task_id,
+            &HashMap::new(),
+        )
+        .unwrap();
 
         assert_eq!(formatted_message, expected);
     }
 
+    #[test]
+    fn test_format_commit_message_keeps_empty_lines_written_on_purpose() {
+        let template =
+            "{subject}\n\n{% if body %}{body}{% endif %}\n\n{task_id}";
+        let formatted_message = format_commit_message(
+            template,
+            "Subject",
+            "",
+            "TEST-111",
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(formatted_message, "Subject\n\n\n\nTEST-111");
+
+        let template = "{subject}\n\n{body}\n\n{task_id}";
+        let formatted_message = format_commit_message(
+            template,
+            "Subject",
+            "First\n\n\n\nSecond",
+            "TEST-111",
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            formatted_message,
+            "Subject\n\nFirst\n\n\n\nSecond\n\nTEST-111"
+        );
+    }
+
     #[test]
     fn test_providing_task_id_into_commit_message() {
         let branch_name = "test/ABC-111-test";
@@ -362,12 +1425,13 @@ mod tests {
         let path = file.into_temp_path();
         let path = path.to_str().unwrap();
 
-        provide_task_id_into_commit(
-            task_regex,
-            commit_message_template,
-            path,
-            branch_name,
-        );
+        let injector = TaskIdInjector::builder()
+            .task_regex(task_regex)
+            .template(commit_message_template)
+            .comment_string("#")
+            .build()
+            .unwrap();
+        injector.update_file(path, branch_name).unwrap();
         let commit_message = read_to_string(path).unwrap_or_default();
 
         assert_eq!(commit_message, expected);
@@ -387,12 +1451,13 @@ mod tests {
         let path = file.into_temp_path();
         let path = path.to_str().unwrap();
 
-        provide_task_id_into_commit(
-            task_regex,
-            commit_message_template,
-            path,
-            branch_name,
-        );
+        let injector = TaskIdInjector::builder()
+            .task_regex(task_regex)
+            .template(commit_message_template)
+            .comment_string("#")
+            .build()
+            .unwrap();
+        injector.update_file(path, branch_name).unwrap();
         let commit_message = read_to_string(path).unwrap_or_default();
 
         assert_eq!(commit_message, expected);
@@ -413,24 +1478,721 @@ mod tests {
         let path = commit_message_file.into_temp_path();
         let path = path.to_str().unwrap();
 
-        provide_task_id_into_commit(
-            task_regex,
-            commit_message_template,
-            path,
-            branch_name,
+        let injector = TaskIdInjector::builder()
+            .task_regex(task_regex)
+            .template(commit_message_template)
+            .comment_string("#")
+            .build()
+            .unwrap();
+        injector.update_file(path, branch_name).unwrap();
+        let commit_message = read_to_string(path).unwrap_or_default();
+
+        assert_eq!(commit_message, expected);
+    }
+
+    #[test]
+    fn test_providing_all_task_ids_into_commit_message() {
+        let branch_name = "feature/ABC-12-ABC-13-shared-fix";
+        let task_regex = r"(?<task_template>ABC-\d+)";
+
+        let commit_message = "Commit subject\n\nFixes ABC-13";
+        let commit_message_template = "{subject}\n\n{body}\n\nRefs: {task_id}";
+        let expected = "Commit subject\n\nFixes ABC-13\n\nRefs: ABC-12";
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{}", commit_message).unwrap();
+        let path = file.into_temp_path();
+        let path = path.to_str().unwrap();
+
+        let injector = TaskIdInjector::builder()
+            .task_regex(task_regex)
+            .template(commit_message_template)
+            .comment_string("#")
+            .all_task_ids(true)
+            .task_id_separator("\nRefs: ")
+            .build()
+            .unwrap();
+        injector.update_file(path, "feature/ABC-12-ABC-14").unwrap();
+        let commit_message = read_to_string(path).unwrap_or_default();
+
+        assert_eq!(
+            commit_message,
+            "Commit subject\n\nFixes ABC-13\n\nRefs: ABC-12\nRefs: ABC-14"
+        );
+
+        write!(
+            File::create(path).unwrap(),
+            "Commit subject\n\nFixes ABC-13"
+        )
+        .unwrap();
+        injector.update_file(path, branch_name).unwrap();
+        let commit_message = read_to_string(path).unwrap_or_default();
+
+        assert_eq!(commit_message, expected);
+    }
+
+    #[test]
+    fn test_preparing_empty_commit_message_is_opt_in() {
+        let branch_name = "test/ABC-111-test";
+        let task_regex = r"test/(?<task_template>ABC-\d+).*";
+        let commit_message_template = "{subject}\n\n{body}\n\n{task_id}";
+
+        let commit_message = "\n# Please enter the commit message.\n#\n\
+            # ------------------------ >8 ------------------------\n\
+            diff --git a/file b/file\n";
+        let expected =
+            "\n\nABC-111\n\n# Please enter the commit message.\n#\n\
+            # ------------------------ >8 ------------------------\n\
+            diff --git a/file b/file\n";
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", commit_message).unwrap();
+        let path = file.into_temp_path();
+        let path = path.to_str().unwrap();
+
+        let builder = || {
+            TaskIdInjector::builder()
+                .task_regex(task_regex)
+                .template(commit_message_template)
+                .comment_string("#")
+        };
+        // Empty message must still abort the commit
+        let injector = builder().prefill_empty(true).build().unwrap();
+        assert!(matches!(
+            injector
+                .update_message(commit_message, branch_name)
+                .unwrap(),
+            Outcome::Skipped(..)
+        ));
+        let injector = builder().build().unwrap();
+        assert!(matches!(
+            injector.prepare_file(path, branch_name, None).unwrap(),
+            Outcome::Skipped(..)
+        ));
+        assert_eq!(read_to_string(path).unwrap(), commit_message);
+
+        let injector = builder().prefill_empty(true).build().unwrap();
+        injector.prepare_file(path, branch_name, None).unwrap();
+        let commit_message = read_to_string(path).unwrap_or_default();
+
+        assert_eq!(commit_message, expected);
+    }
+
+    #[test]
+    fn test_preparing_commit_message_skips_merge_and_amend() {
+        let branch_name = "test/ABC-111-test";
+        let task_regex = r"test/(?<task_template>ABC-\d+).*";
+        let commit_message_template = "{subject}\n\n{body}\n\n{task_id}";
+        let commit_message = "Merge branch 'main'\n# Comment\n";
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", commit_message).unwrap();
+        let path = file.into_temp_path();
+        let path = path.to_str().unwrap();
+
+        let injector = TaskIdInjector::builder()
+            .task_regex(task_regex)
+            .template(commit_message_template)
+            .comment_string("#")
+            .build()
+            .unwrap();
+        for commit_source in ["merge", "commit"] {
+            injector
+                .prepare_file(path, branch_name, Some(commit_source))
+                .unwrap();
+            let commit_message_after =
+                read_to_string(path).unwrap_or_default();
+
+            assert_eq!(commit_message_after, commit_message);
+        }
+    }
+
+    #[test]
+    fn test_split_commit_message() {
+        let commit_message =
+            "# Leading\nSubject\n# Inner comment\nBody\n\n# Comment\n";
+        let expected = CommitMessageParts {
+            leading: "# Leading\n",
+            user_message: "Subject\n# Inner comment\nBody\n",
+            trailing: "\n# Comment\n",
+        };
+
+        assert_eq!(split_commit_message(commit_message, "#"), expected);
+    }
+
+    #[test]
+    fn test_splice_keeps_inner_comments_in_place() {
+        let parts = split_commit_message(
+            "fix: thing\n# First\nBody\n# Second\nMore\n# Last\n",
+            "#",
         );
+
+        // Replaced subject keeps the comment, the new line goes after it
+        assert_eq!(
+            parts.splice("fix(ABC-1): thing\n\nBody\nMore\n\nABC-1", "#"),
+            "fix(ABC-1): thing\n# First\n\nBody\n# Second\nMore\n\nABC-1\n\
+            # Last\n"
+        );
+        assert_eq!(
+            parts.splice("ABC-1", "#"),
+            "ABC-1\n# First\n# Second\n# Last\n"
+        );
+    }
+
+    #[test]
+    fn test_split_commit_message_without_user_message() {
+        let commit_message = "\n# Comment\n#\n";
+        let expected = CommitMessageParts {
+            leading: "",
+            user_message: "",
+            trailing: "\n# Comment\n#\n",
+        };
+
+        assert_eq!(split_commit_message(commit_message, "#"), expected);
+    }
+
+    #[test]
+    fn test_providing_task_id_keeps_comments_and_cutline_section() {
+        let branch_name = "test/ABC-111-test";
+        let task_regex = r"test/(?<task_template>ABC-\d+).*";
+        let commit_message_template = "{subject}\n\n{body}\n\n{task_id}";
+
+        let commit_message = "# Leading\nSubject\n# Inner\n\nBody\n\n\
+            # Comment\n# ------------------------ >8 ------------------------\n\
+            diff --git a/file b/file\n+# added line\n";
+        let expected = "# Leading\nSubject\n# Inner\n\nBody\n\nABC-111\n\n\
+            # Comment\n# ------------------------ >8 ------------------------\n\
+            diff --git a/file b/file\n+# added line\n";
+
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "{}", commit_message).unwrap();
+        let path = file.into_temp_path();
+        let path = path.to_str().unwrap();
+
+        let injector = TaskIdInjector::builder()
+            .task_regex(task_regex)
+            .template(commit_message_template)
+            .comment_string("#")
+            .build()
+            .unwrap();
+        injector.update_file(path, branch_name).unwrap();
         let commit_message = read_to_string(path).unwrap_or_default();
 
         assert_eq!(commit_message, expected);
     }
 
+    #[test]
+    fn test_task_id_without_literal_is_only_inserted() {
+        let builder = || {
+            TaskIdInjector::builder()
+                .task_regex(r"feature/(?P<task_template>\d+)-.*")
+                .template("{subject}\n\n#{task_id}")
+                .comment_string("#")
+        };
+
+        let injector = builder().build().unwrap();
+        assert_eq!(
+            injector
+                .update_message("Bump to 2", "feature/12-bump")
+                .unwrap(),
+            Outcome::Updated(String::from("Bump to 2\n\n#12"))
+        );
+
+        // Searching message for any task id needs explicit regex
+        assert!(matches!(
+            injector.check_message("Bump to 2", "hotfix"),
+            Err(Error::Config(ConfigError::AmbiguousTaskIdRegex(..)))
+        ));
+        let injector =
+            builder().other_task_id(OtherTaskId::Fail).build().unwrap();
+        assert!(matches!(
+            injector.update_message("Bump to 2", "feature/12-bump"),
+            Err(Error::Config(ConfigError::AmbiguousTaskIdRegex(..)))
+        ));
+
+        let injector = builder().task_id_regex(r"#\d+").build().unwrap();
+        assert!(injector.check_message("Fix #3", "hotfix").is_ok());
+    }
+
+    #[test]
+    fn test_injector_outcomes() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+            .template("{subject} ({task_id})")
+            .comment_string("#")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            injector
+                .update_message("Subject\n", "feature/ABC-1")
+                .unwrap(),
+            Outcome::Updated(String::from("Subject (ABC-1)"))
+        );
+        assert_eq!(
+            injector
+                .update_message("Subject ABC-1", "feature/ABC-1")
+                .unwrap(),
+            Outcome::AlreadyContainsTaskId
+        );
+        assert_eq!(
+            injector.update_message("Subject", "main").unwrap(),
+            Outcome::NoTaskId(TaskIDError::NotInBranch)
+        );
+    }
+
+    #[test]
+    fn test_providing_named_groups_into_commit_message() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"(?P<type>feat|fix)/(?P<task_template>ABC-\d+)")
+            .template("{type}({task_id}): {subject}")
+            .comment_string("#")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            injector
+                .update_message("Subject\n", "fix/ABC-1-login")
+                .unwrap(),
+            Outcome::Updated(String::from("fix(ABC-1): Subject"))
+        );
+    }
+
+    #[test]
+    fn test_providing_task_id_as_trailer() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+            .trailer("Refs")
+            .comment_string("#")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            injector
+                .update_message(
+                    "Subject\n\nBody\n\nSigned-off-by: A <a@example.com>\n# Comment\n",
+                    "feature/ABC-1"
+                )
+                .unwrap(),
+            Outcome::Updated(String::from(
+                "Subject\n\nBody\n\nRefs: ABC-1\nSigned-off-by: A <a@example.com>\n# Comment\n"
+            ))
+        );
+        assert!(matches!(
+            TaskIdInjector::builder()
+                .task_regex(".*")
+                .trailer("Task ID")
+                .build(),
+            Err(Error::Config(ConfigError::InvalidTrailer(..)))
+        ));
+    }
+
+    #[test]
+    fn test_providing_task_id_into_conventional_subject() {
+        let builder = || {
+            TaskIdInjector::builder()
+                .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+                .conventional(conventional::Placement::AppendScope)
+                .comment_string("#")
+        };
+        let injector = builder().build().unwrap();
+
+        assert_eq!(
+            injector
+                .update_message("feat(api): Subject\n\nBody", "feature/ABC-1")
+                .unwrap(),
+            Outcome::Updated(String::from(
+                "feat(api, ABC-1): Subject\n\nBody"
+            ))
+        );
+        assert!(matches!(
+            injector.update_message("Subject", "feature/ABC-1").unwrap(),
+            Outcome::Skipped(..)
+        ));
+
+        // Subject stays on one line whatever separator of task ids is set
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"(?P<task_template>ABC-\d+)")
+            .conventional(conventional::Placement::AppendScope)
+            .all_task_ids(true)
+            .task_id_separator("\n")
+            .comment_string("#")
+            .build()
+            .unwrap();
+        assert_eq!(
+            injector
+                .update_message("feat(api): Subject", "feature/ABC-1-ABC-2-x")
+                .unwrap(),
+            Outcome::Updated(String::from("feat(api, ABC-1, ABC-2): Subject"))
+        );
+
+        let injector = builder()
+            .non_conventional(NonConventional::Fail)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            injector.update_message("Subject", "feature/ABC-1"),
+            Err(Error::NotConventional(..))
+        ));
+    }
+
+    #[test]
+    fn test_contains_task_id_as_whole_word() {
+        assert!(contains_task_id("Fix ABC-1.", "ABC-1", false));
+        assert!(contains_task_id("[ABC-1] Fix", "ABC-1", false));
+        assert!(!contains_task_id("Fix ABC-12", "ABC-1", false));
+        assert!(!contains_task_id("Fix XABC-1", "ABC-1", false));
+        assert!(!contains_task_id("Fix abc-1", "ABC-1", false));
+        assert!(contains_task_id("Fix abc-1", "ABC-1", true));
+    }
+
+    #[test]
+    fn test_other_task_id_in_commit_message() {
+        let builder = || {
+            TaskIdInjector::builder()
+                .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+                .template("{subject} ({task_id})")
+                .comment_string("#")
+        };
+
+        assert_eq!(
+            builder()
+                .build()
+                .unwrap()
+                .update_message("Subject ABC-12", "feature/ABC-1")
+                .unwrap(),
+            Outcome::Updated(String::from("Subject ABC-12 (ABC-1)"))
+        );
+        assert!(matches!(
+            builder()
+                .other_task_id(OtherTaskId::Skip)
+                .build()
+                .unwrap()
+                .update_message("Subject ABC-12", "feature/ABC-1")
+                .unwrap(),
+            Outcome::Skipped(..)
+        ));
+
+        let injector = builder()
+            .other_task_id(OtherTaskId::Fail)
+            .ignore_case(true)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            injector.update_message("Subject abc-12", "feature/ABC-1"),
+            Err(Error::OtherTaskId(..))
+        ));
+        assert_eq!(
+            injector
+                .update_message("Subject abc-1", "feature/ABC-1")
+                .unwrap(),
+            Outcome::AlreadyContainsTaskId
+        );
+    }
+
+    #[test]
+    fn test_task_id_normalization() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>[A-Za-z]+[-_]?\d+)-.*")
+            .template("{subject} ({task_id})")
+            .normalization(Normalization {
+                case: Some(normalize::Case::Upper),
+                key_separator: Some(String::from("-")),
+                number_width: Some(0),
+            })
+            .task_id_regex(r"[A-Za-z]+[-_]?\d+")
+            .other_task_id(OtherTaskId::Fail)
+            .comment_string("#")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            injector.task_ids("feature/abc_012-x/ABC-12-y").unwrap(),
+            vec![String::from("ABC-12")]
+        );
+        assert_eq!(
+            injector
+                .update_message("Subject", "feature/abc_12-x")
+                .unwrap(),
+            Outcome::Updated(String::from("Subject (ABC-12)"))
+        );
+        assert_eq!(
+            injector
+                .update_message("Subject abc12", "feature/abc_12-x")
+                .unwrap(),
+            Outcome::AlreadyContainsTaskId
+        );
+    }
+
+    #[test]
+    fn test_project_key_aliases() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>[A-Za-z]+-\d+)")
+            .template("{subject} ({task_id})")
+            .normalization(Normalization {
+                case: Some(normalize::Case::Upper),
+                ..Default::default()
+            })
+            .alias("WEB", "WEBAPP")
+            .alias("OLD", "NEW")
+            .comment_string("#")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            injector.task_ids("feature/web-12").unwrap(),
+            vec![String::from("WEBAPP-12")]
+        );
+        assert_eq!(
+            injector.update_message("Subject", "feature/old-3").unwrap(),
+            Outcome::Updated(String::from("Subject (NEW-3)"))
+        );
+        assert_eq!(
+            injector
+                .update_message("Subject OLD-3", "feature/old-3")
+                .unwrap(),
+            Outcome::AlreadyContainsTaskId
+        );
+    }
+
+    #[test]
+    fn test_skipping_autosquash_and_merge_commits() {
+        let git_dir = tempfile::tempdir().unwrap();
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+            .template("{subject} ({task_id})")
+            .require(true)
+            .comment_string("#")
+            .git_dir(git_dir.path())
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            injector
+                .update_message("fixup! Subject", "feature/ABC-1")
+                .unwrap(),
+            Outcome::Skipped(..)
+        ));
+        assert!(injector.check_message("squash! Subject", "hotfix").is_ok());
+
+        File::create(git_dir.path().join("MERGE_HEAD")).unwrap();
+        assert!(matches!(
+            injector.update_message("Subject", "feature/ABC-1").unwrap(),
+            Outcome::Skipped(..)
+        ));
+
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+            .template("{subject} ({task_id})")
+            .skip_rule(SkipRule::Revert)
+            .comment_string("#")
+            .git_dir(git_dir.path())
+            .build()
+            .unwrap();
+        assert_eq!(
+            injector
+                .update_message("fixup! Subject", "feature/ABC-1")
+                .unwrap(),
+            Outcome::Updated(String::from("fixup! Subject (ABC-1)"))
+        );
+    }
+
+    #[test]
+    fn test_commit_message_is_returned_without_writing() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+            .template("{subject} ({task_id})")
+            .require(true)
+            .comment_string("#")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            injector.commit_message("Subject", "feature/ABC-1").unwrap(),
+            Outcome::Updated(String::from("Subject (ABC-1)"))
+        );
+        assert!(matches!(
+            injector.commit_message("Subject", "hotfix"),
+            Err(Error::MissingTaskId(..))
+        ));
+        assert!(matches!(
+            injector
+                .prepare_message("Subject", "feature/ABC-1", Some("merge"))
+                .unwrap(),
+            Outcome::Skipped(..)
+        ));
+    }
+
+    #[test]
+    fn test_task_match_exit_codes() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"(?P<type>fix|feat)/(?P<task_template>ABC-\d+)")
+            .task_regex(r"hotfix/\d+")
+            .build()
+            .unwrap();
+
+        let task_match = injector.task_match("fix/ABC-1").unwrap();
+        assert_eq!(task_match.rule, 0);
+        assert_eq!(task_match.groups["type"], "fix");
+        assert_eq!(injector.task_match("main").unwrap_err().exit_code(), 3);
+        assert_eq!(
+            injector.task_match("hotfix/1").unwrap_err().exit_code(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_check_commit() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+            .comment_string("#")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            injector
+                .check_commit("Subject\n\nRefs: ABC-1\n", 1)
+                .unwrap(),
+            CommitCheck::HasTaskId
+        );
+        // Stored message has no comments, `#` lines are written by user
+        assert_eq!(
+            injector
+                .check_commit("Subject\n\n# ABC-2 heading\n", 1)
+                .unwrap(),
+            CommitCheck::HasTaskId
+        );
+        assert_eq!(
+            injector.check_commit("Subject ABC-12x\n", 1).unwrap(),
+            CommitCheck::MissingTaskId
+        );
+        assert!(matches!(
+            injector.check_commit("fixup! Subject\n", 1).unwrap(),
+            CommitCheck::Skipped(..)
+        ));
+        assert!(matches!(
+            injector.check_commit("Subject\n", 2).unwrap(),
+            CommitCheck::Skipped(..)
+        ));
+    }
+
+    #[test]
+    fn test_update_commit_keeps_comment_like_lines() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+            .template("{subject}\n\n{body}\n\n{task_id}")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            injector
+                .update_commit(
+                    "Fix thing\n\n#42 was the issue\nmore text\n",
+                    1,
+                    "feature/ABC-1"
+                )
+                .unwrap(),
+            Outcome::Updated(String::from(
+                "Fix thing\n\n#42 was the issue\nmore text\n\nABC-1"
+            ))
+        );
+        assert!(matches!(
+            injector
+                .update_commit("Merge branch 'main'", 2, "feature/ABC-1")
+                .unwrap(),
+            Outcome::Skipped(..)
+        ));
+    }
+
+    #[test]
+    fn test_check_branch() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+            .build()
+            .unwrap();
+
+        assert!(injector.check_branch("feature/ABC-1-login").is_ok());
+        assert!(injector.check_branch("release/1.0").is_ok());
+        let err = injector.check_branch("feature/login").unwrap_err();
+        assert!(matches!(err, Error::MissingTaskId(..)));
+        assert!(err
+            .to_string()
+            .contains(r"feature/(?P<task_template>ABC-\d+)"));
+
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/ABC-\d+")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            injector.check_branch("feature/ABC-1"),
+            Err(Error::TaskId(TaskIDError::WrongCapturingGroup))
+        ));
+    }
+
+    #[test]
+    fn test_branch_name() {
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)-.*")
+            .build()
+            .unwrap();
+        assert_eq!(
+            injector
+                .branch_name("ABC-12", "Fix login redirect!")
+                .unwrap(),
+            "feature/ABC-12-fix-login-redirect"
+        );
+        assert!(matches!(
+            injector.branch_name("XYZ-1", "Fix"),
+            Err(Error::MissingTaskId(..))
+        ));
+
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"(?P<task_template>ABC-\d+)")
+            .branch_template("{task_id|lower}/{slug}")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            injector.branch_name("ABC-1", "Fix"),
+            Err(Error::MissingTaskId(..))
+        ));
+    }
+
+    #[test]
+    fn test_injector_errors() {
+        let build_error = TaskIdInjector::builder()
+            .task_regex("(?P<task_template>")
+            .build()
+            .unwrap_err();
+        assert!(matches!(build_error, Error::Regex(..)));
+        assert_eq!(build_error.exit_code(), 2);
+
+        let injector = TaskIdInjector::builder()
+            .task_regex(r"feature/(?P<task_template>ABC-\d+)")
+            .template("{subject} {unknown}")
+            .comment_string("#")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            injector.update_message("Subject", "feature/ABC-1"),
+            Err(Error::Template(..))
+        ));
+        assert!(matches!(
+            injector.check_message("Subject", "hotfix-foo"),
+            Err(Error::MissingTaskId(..))
+        ));
+        assert!(matches!(
+            injector.update_file("/nonexistent/COMMIT_EDITMSG", "main"),
+            Err(Error::Io(..))
+        ));
+    }
+
     #[test]
     fn test_removing_of_comment_section_in_beginning() {
         let commit_message = "# Comment\nSubject\nBody";
         let expected = "Subject\nBody";
 
         let commit_message_without_comments =
-            get_commit_message_without_comments(commit_message);
+            get_commit_message_without_comments(commit_message, "#");
 
         assert_eq!(commit_message_without_comments, expected)
     }
@@ -441,7 +2203,7 @@ mod tests {
         let expected = "Subject\nBody";
 
         let commit_message_without_comments =
-            get_commit_message_without_comments(commit_message);
+            get_commit_message_without_comments(commit_message, "#");
 
         assert_eq!(commit_message_without_comments, expected)
     }
@@ -452,7 +2214,7 @@ mod tests {
         let expected = "Subject\nBody";
 
         let commit_message_without_comments =
-            get_commit_message_without_comments(commit_message);
+            get_commit_message_without_comments(commit_message, "#");
 
         assert_eq!(commit_message_without_comments, expected)
     }
@@ -464,7 +2226,7 @@ mod tests {
         let expected = "Subject\nBody";
 
         let commit_message_without_comments =
-            get_commit_message_without_comments(commit_message);
+            get_commit_message_without_comments(commit_message, "#");
 
         assert_eq!(commit_message_without_comments, expected)
     }
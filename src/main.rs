@@ -1,3 +1,11 @@
-fn main() {
-    pyrust_task_id::parse_args_and_run();
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match pyrust_task_id::parse_args_and_run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(err.exit_code())
+        }
+    }
 }
@@ -0,0 +1,168 @@
+//! Canonical form of task ids
+//!
+//! Branches `feature/abc-123`, `feature/ABC_123` and `feature/abc123` refer to
+//! the same task. Normalization turns every spelling into one form before it
+//! is inserted or searched in commit message. Project key aliases are
+//! resolved afterwards, so `web-12` may become `WEBAPP-12`.
+
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Return regex splitting task id into project key, separator and number
+fn get_task_id_regex() -> Regex {
+    Regex::new(
+        r"^(?P<key>[[:alpha:]][[:alnum:]]*?)(?P<separator>[-_ .]?)(?P<number>[0-9]+)$",
+    )
+    .unwrap()
+}
+
+/// Case task id is converted to
+#[derive(Deserialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Case {
+    Upper,
+    Lower,
+}
+
+/// Rules turning task id into its canonical form, unset rules change nothing
+#[derive(Deserialize, Default, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Normalization {
+    /// Case of the whole task id
+    pub case: Option<Case>,
+    /// String between project key and number, e.g. `-`
+    pub key_separator: Option<String>,
+    /// Number is padded with zeros to this width, `0` strips leading zeros
+    pub number_width: Option<usize>,
+}
+
+impl Normalization {
+    /// Return rules where rules from `overrides` take precedence
+    ///
+    /// * `overrides` - rules which should replace current ones
+    pub fn merge(self, overrides: Normalization) -> Normalization {
+        Normalization {
+            case: overrides.case.or(self.case),
+            key_separator: overrides.key_separator.or(self.key_separator),
+            number_width: overrides.number_width.or(self.number_width),
+        }
+    }
+
+    /// Return canonical form of the task id
+    ///
+    /// Separator and number rules are applied only to task ids looking like
+    /// `<key><separator><number>`, e.g. `ABC-123`.
+    ///
+    /// * `task_id` - task id as it is written
+    pub fn apply(&self, task_id: &str) -> String {
+        let mut result = task_id.to_string();
+
+        if self.key_separator.is_some() || self.number_width.is_some() {
+            if let Some(captures) = get_task_id_regex().captures(task_id) {
+                let separator = self
+                    .key_separator
+                    .as_deref()
+                    .unwrap_or(&captures["separator"]);
+                let number = match self.number_width {
+                    Some(width) => {
+                        let number =
+                            match captures["number"].trim_start_matches('0') {
+                                "" => "0",
+                                number => number,
+                            };
+                        format!("{number:0>width$}")
+                    }
+                    None => captures["number"].to_string(),
+                };
+                result = format!("{}{separator}{number}", &captures["key"]);
+            }
+        }
+
+        match self.case {
+            Some(Case::Upper) => result.to_uppercase(),
+            Some(Case::Lower) => result.to_lowercase(),
+            None => result,
+        }
+    }
+}
+
+/// Return task id with project key replaced by the one it is an alias of
+///
+/// Keys are compared ignoring case, separator and number are kept.
+///
+/// * `task_id` - task id, usually already normalized
+/// * `aliases` - canonical project key by alias or legacy key
+pub fn resolve_alias(
+    task_id: &str,
+    aliases: &HashMap<String, String>,
+) -> String {
+    let Some(captures) = get_task_id_regex().captures(task_id) else {
+        return task_id.to_string();
+    };
+    let key = &captures["key"];
+
+    match aliases
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(key))
+    {
+        Some((_, canonical_key)) => format!(
+            "{canonical_key}{}{}",
+            &captures["separator"], &captures["number"]
+        ),
+        None => task_id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_normalization_keeps_task_id() {
+        let normalization = Normalization::default();
+
+        assert_eq!(normalization.apply("abc_007"), "abc_007");
+    }
+
+    #[test]
+    fn test_normalize_spellings() {
+        let normalization = Normalization {
+            case: Some(Case::Upper),
+            key_separator: Some(String::from("-")),
+            number_width: Some(0),
+        };
+
+        for task_id in ["abc-123", "ABC_123", "abc123", "Abc 0123"] {
+            assert_eq!(normalization.apply(task_id), "ABC-123");
+        }
+        assert_eq!(normalization.apply("web2-12"), "WEB2-12");
+        assert_eq!(normalization.apply("abc-000"), "ABC-0");
+        assert_eq!(normalization.apply("#12"), "#12");
+    }
+
+    #[test]
+    fn test_resolve_alias() {
+        let aliases = HashMap::from([
+            (String::from("web"), String::from("WEBAPP")),
+            (String::from("OLD"), String::from("NEW")),
+        ]);
+
+        assert_eq!(resolve_alias("WEB-12", &aliases), "WEBAPP-12");
+        assert_eq!(resolve_alias("old_7", &aliases), "NEW_7");
+        assert_eq!(resolve_alias("WEBAPP-12", &aliases), "WEBAPP-12");
+        assert_eq!(resolve_alias("OLDER-1", &aliases), "OLDER-1");
+    }
+
+    #[test]
+    fn test_normalize_number_width() {
+        let normalization = Normalization {
+            number_width: Some(4),
+            ..Default::default()
+        };
+
+        assert_eq!(normalization.apply("ABC-12"), "ABC-0012");
+        assert_eq!(normalization.apply("ABC-000012"), "ABC-0012");
+        assert_eq!(normalization.apply("ABC-123456"), "ABC-123456");
+    }
+}
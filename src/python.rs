@@ -0,0 +1,223 @@
+use crate::normalize::{Case, Normalization};
+use crate::{
+    format_commit_message, get_subject_and_body, Error, TaskIDError,
+    TaskIdInjector,
+};
+use clap::ValueEnum;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyOSError, PyValueError};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+create_exception!(
+    pyrust_task_id,
+    TaskIdError,
+    PyException,
+    "Base class of errors raised by `pyrust_task_id`."
+);
+create_exception!(
+    pyrust_task_id,
+    NotInBranchError,
+    TaskIdError,
+    "Branch name doesn't match any of task regexes."
+);
+create_exception!(
+    pyrust_task_id,
+    WrongCapturingGroupError,
+    TaskIdError,
+    "Task regex doesn't contain `task_template` named capturing group."
+);
+create_exception!(
+    pyrust_task_id,
+    MissingTaskIdError,
+    TaskIdError,
+    "Task id is required but it is missing."
+);
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> Self {
+        let message = err.to_string();
+        match err {
+            Error::TaskId(TaskIDError::NotInBranch) => {
+                NotInBranchError::new_err(message)
+            }
+            Error::TaskId(TaskIDError::WrongCapturingGroup) => {
+                WrongCapturingGroupError::new_err(message)
+            }
+            Error::MissingTaskId(_) => MissingTaskIdError::new_err(message),
+            Error::NotConventional(_) | Error::UnsafeRewrite(_) => {
+                PyValueError::new_err(message)
+            }
+            Error::OtherTaskId(_) => TaskIdError::new_err(message),
+            Error::Io(..) | Error::Git(_) => PyOSError::new_err(message),
+            Error::Template(_) | Error::Regex(..) | Error::Config(_) => {
+                PyValueError::new_err(message)
+            }
+        }
+    }
+}
+
+/// Return task ids found in branch name the same way the hook does
+///
+/// * `branch` - branch name
+/// * `patterns` - regexes with `task_template` named capturing group
+/// * `all_task_ids` - whether every task id is returned or only the first
+/// * `normalization` - rules turning task id into its canonical form
+/// * `aliases` - canonical project key by alias
+fn task_ids(
+    branch: &str,
+    patterns: Vec<String>,
+    all_task_ids: bool,
+    normalization: Normalization,
+    aliases: Option<HashMap<String, String>>,
+) -> Result<Vec<String>, Error> {
+    let mut builder = TaskIdInjector::builder()
+        .all_task_ids(all_task_ids)
+        .normalization(normalization);
+    for pattern in patterns {
+        builder = builder.task_regex(pattern);
+    }
+    for (alias, canonical_key) in aliases.unwrap_or_default() {
+        builder = builder.alias(alias, canonical_key);
+    }
+
+    builder.build()?.task_ids(branch)
+}
+
+/// Return normalization rules passed from Python
+///
+/// * `case` - `"upper"` or `"lower"`
+/// * `key_separator` - string between project key and number
+/// * `number_width` - width number is padded to
+fn normalization(
+    case: Option<&str>,
+    key_separator: Option<String>,
+    number_width: Option<usize>,
+) -> PyResult<Normalization> {
+    let case = case
+        .map(|case| {
+            Case::from_str(case, true).map_err(|_| {
+                PyValueError::new_err(format!(
+                    "Case `{case}` is unknown, expected `upper` or `lower`."
+                ))
+            })
+        })
+        .transpose()?;
+
+    Ok(Normalization {
+        case,
+        key_separator,
+        number_width,
+    })
+}
+
+/// Return every task id found in branch name by the first matching pattern
+///
+/// Task ids are normalized and aliases are resolved the same way as with
+/// `normalize` table and `aliases` of the config.
+#[pyfunction]
+#[pyo3(signature = (
+    branch,
+    patterns,
+    *,
+    normalize_case = None,
+    normalize_key_separator = None,
+    normalize_number_width = None,
+    aliases = None,
+))]
+fn extract_task_ids(
+    branch: &str,
+    patterns: Vec<String>,
+    normalize_case: Option<&str>,
+    normalize_key_separator: Option<String>,
+    normalize_number_width: Option<usize>,
+    aliases: Option<HashMap<String, String>>,
+) -> PyResult<Vec<String>> {
+    let normalization = normalization(
+        normalize_case,
+        normalize_key_separator,
+        normalize_number_width,
+    )?;
+
+    Ok(task_ids(branch, patterns, true, normalization, aliases)?)
+}
+
+/// Return the first task id found in branch name
+///
+/// Accepts the same keyword options as `extract_task_ids`.
+#[pyfunction]
+#[pyo3(signature = (
+    branch,
+    patterns,
+    *,
+    normalize_case = None,
+    normalize_key_separator = None,
+    normalize_number_width = None,
+    aliases = None,
+))]
+fn extract_task_id(
+    branch: &str,
+    patterns: Vec<String>,
+    normalize_case: Option<&str>,
+    normalize_key_separator: Option<String>,
+    normalize_number_width: Option<usize>,
+    aliases: Option<HashMap<String, String>>,
+) -> PyResult<String> {
+    let normalization = normalization(
+        normalize_case,
+        normalize_key_separator,
+        normalize_number_width,
+    )?;
+    let mut task_ids =
+        task_ids(branch, patterns, false, normalization, aliases)?;
+
+    Ok(task_ids.remove(0))
+}
+
+/// Return subject and body of commit message without comments
+#[pyfunction]
+#[pyo3(signature = (text, comment_string = "#"))]
+fn parse_message(text: &str, comment_string: &str) -> (String, String) {
+    get_subject_and_body(text.trim(), comment_string)
+}
+
+/// Render commit message template
+///
+/// Named groups of task regex may be passed as extra placeholders.
+#[pyfunction]
+#[pyo3(signature = (template, subject, body, task_id, groups = None))]
+fn render(
+    template: &str,
+    subject: &str,
+    body: &str,
+    task_id: &str,
+    groups: Option<HashMap<String, String>>,
+) -> Result<String, Error> {
+    format_commit_message(
+        template,
+        subject,
+        body,
+        task_id,
+        &groups.unwrap_or_default(),
+    )
+}
+
+#[pymodule]
+#[pyo3(name = "pyrust_task_id")]
+fn init_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add("TaskIdError", py.get_type::<TaskIdError>())?;
+    module.add("NotInBranchError", py.get_type::<NotInBranchError>())?;
+    module.add(
+        "WrongCapturingGroupError",
+        py.get_type::<WrongCapturingGroupError>(),
+    )?;
+    module.add("MissingTaskIdError", py.get_type::<MissingTaskIdError>())?;
+
+    module.add_function(wrap_pyfunction!(extract_task_id, module)?)?;
+    module.add_function(wrap_pyfunction!(extract_task_ids, module)?)?;
+    module.add_function(wrap_pyfunction!(parse_message, module)?)?;
+    module.add_function(wrap_pyfunction!(render, module)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+//! Rules of commits which are left without task id
+
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Kind of commit which is left untouched
+#[derive(Deserialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipRule {
+    /// Subject starts with `fixup!`, `squash!` or `amend!`
+    Autosquash,
+    /// Subject is generated by `git merge` or `git pull`
+    Merge,
+    /// Subject is generated by `git revert`
+    Revert,
+    /// Merge is in progress, `MERGE_HEAD` exists
+    MergeHead,
+    /// Cherry-pick is in progress, `CHERRY_PICK_HEAD` exists
+    CherryPick,
+}
+
+/// Rules used if they aren't set in config
+pub const DEFAULT_SKIP_RULES: [SkipRule; 5] = [
+    SkipRule::Autosquash,
+    SkipRule::Merge,
+    SkipRule::Revert,
+    SkipRule::MergeHead,
+    SkipRule::CherryPick,
+];
+
+impl SkipRule {
+    /// Return name of the rule as it is written in config
+    fn name(&self) -> &'static str {
+        match self {
+            SkipRule::Autosquash => "autosquash",
+            SkipRule::Merge => "merge",
+            SkipRule::Revert => "revert",
+            SkipRule::MergeHead => "merge-head",
+            SkipRule::CherryPick => "cherry-pick",
+        }
+    }
+
+    /// Return regex of the subject matched by the rule
+    fn subject_regex(&self) -> Option<Regex> {
+        let pattern = match self {
+            SkipRule::Autosquash => r"^(fixup|squash|amend)! ",
+            SkipRule::Merge => {
+                r"^Merge (branch|branches|remote-tracking branch|tag|commit|pull request) "
+            }
+            SkipRule::Revert => r#"^Revert ""#,
+            SkipRule::MergeHead | SkipRule::CherryPick => return None,
+        };
+
+        Some(Regex::new(pattern).unwrap())
+    }
+
+    /// Return file in git dir which exists while operation is in progress
+    fn state_file(&self) -> Option<&'static str> {
+        match self {
+            SkipRule::MergeHead => Some("MERGE_HEAD"),
+            SkipRule::CherryPick => Some("CHERRY_PICK_HEAD"),
+            _ => None,
+        }
+    }
+}
+
+/// Return reason to leave commit message untouched if any rule matches
+///
+/// * `rules` - rules to check
+/// * `commit_subject` - subject of the commit message
+/// * `git_dir` - git dir of the current worktree, state files are not
+///   checked if it is unknown
+pub fn get_skip_reason(
+    rules: &[SkipRule],
+    commit_subject: &str,
+    git_dir: Option<&Path>,
+) -> Option<String> {
+    rules.iter().find_map(|rule| {
+        if rule
+            .subject_regex()
+            .is_some_and(|regex| regex.is_match(commit_subject))
+        {
+            return Some(format!(
+                "Subject `{commit_subject}` is left untouched by `{}` rule.",
+                rule.name()
+            ));
+        }
+
+        let state_file = rule.state_file()?;
+        git_dir?.join(state_file).is_file().then(|| {
+            format!("Commit message is left untouched while `{state_file}` exists.")
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_skip_by_subject() {
+        let skipped = |subject| {
+            get_skip_reason(&DEFAULT_SKIP_RULES, subject, None).is_some()
+        };
+
+        assert!(skipped("fixup! Subject"));
+        assert!(skipped("amend! Subject"));
+        assert!(skipped("Merge branch 'main' into feature/ABC-1"));
+        assert!(skipped("Merge pull request #1 from user/branch"));
+        assert!(skipped("Revert \"Subject\""));
+        assert!(!skipped("Subject"));
+        assert!(!skipped("Merge sort implementation"));
+        assert!(
+            get_skip_reason(&[SkipRule::Revert], "fixup! x", None).is_none()
+        );
+    }
+
+    #[test]
+    fn test_skip_by_state_file() {
+        let git_dir = tempdir().unwrap();
+        let reason = || {
+            get_skip_reason(
+                &DEFAULT_SKIP_RULES,
+                "Subject",
+                Some(git_dir.path()),
+            )
+        };
+        assert_eq!(reason(), None);
+
+        write(git_dir.path().join("CHERRY_PICK_HEAD"), "").unwrap();
+        assert!(reason().unwrap().contains("CHERRY_PICK_HEAD"));
+        assert_eq!(
+            get_skip_reason(
+                &[SkipRule::MergeHead],
+                "Subject",
+                Some(git_dir.path())
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,588 @@
+//! Commit message template language
+//!
+//! Placeholders are written as `{name}` and may be followed by filters:
+//! `{subject|trim|truncate(72)}`. Text is inserted conditionally with
+//! `{% if body %}...{% else %}...{% endif %}`, the condition is true when the
+//! value is not empty. `{{` and `}}` insert literal braces.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Error in template with position where it was found
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    /// Line of the template starting from 1
+    pub line: usize,
+    /// Column of the template in characters starting from 1
+    pub column: usize,
+    pub message: String,
+}
+
+impl TemplateError {
+    /// Create error pointing to the byte offset in the template
+    ///
+    /// * `source` - the whole template
+    /// * `offset` - byte offset of the incorrect part
+    /// * `message` - explanation of the error
+    fn new(source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        TemplateError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Message template is incorrect at line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// Transformation applied to placeholder value
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Upper,
+    Lower,
+    Trim,
+    /// Keep at most the given number of characters
+    Truncate(usize),
+    /// Lowercase words joined with `-`
+    Slug,
+    /// Value used if placeholder is empty or unknown
+    Default(String),
+}
+
+impl Filter {
+    /// Apply filter to the value
+    ///
+    /// * `value` - value of the placeholder
+    fn apply(&self, value: String) -> String {
+        match self {
+            Filter::Upper => value.to_uppercase(),
+            Filter::Lower => value.to_lowercase(),
+            Filter::Trim => value.trim().to_string(),
+            Filter::Truncate(length) => value.chars().take(*length).collect(),
+            Filter::Slug => slugify(&value),
+            Filter::Default(default) if value.is_empty() => default.clone(),
+            Filter::Default(_) => value,
+        }
+    }
+}
+
+/// Argument of a filter
+enum Argument {
+    Number(usize),
+    Text(String),
+}
+
+/// Part of parsed template
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Placeholder {
+        name: String,
+        filters: Vec<Filter>,
+        /// Byte offset of the placeholder in template
+        offset: usize,
+    },
+    If {
+        name: String,
+        negated: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// Tag which ends a block of nodes
+#[derive(PartialEq)]
+enum BlockEnd {
+    Else,
+    EndIf,
+}
+
+/// Parsed commit message template
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    source: String,
+    nodes: Vec<Node>,
+}
+
+/// Convert text into lowercase words joined with `-`
+///
+/// * `text` - text to convert
+pub fn slugify(text: &str) -> String {
+    text.split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Recursive descent parser of the template
+struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Return part of the template which is not parsed yet
+    fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    /// Return error pointing to the current position
+    ///
+    /// * `message` - explanation of the error
+    fn error(&self, message: impl Into<String>) -> TemplateError {
+        TemplateError::new(self.source, self.offset, message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume the prefix if the rest of the template starts with it
+    ///
+    /// * `prefix` - expected text
+    fn eat(&mut self, prefix: &str) -> bool {
+        let found = self.rest().starts_with(prefix);
+        if found {
+            self.offset += prefix.len();
+        }
+        found
+    }
+
+    /// Consume the prefix or return error
+    ///
+    /// * `prefix` - expected text
+    fn expect(&mut self, prefix: &str) -> Result<(), TemplateError> {
+        if self.eat(prefix) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected `{prefix}`.")))
+        }
+    }
+
+    /// Consume name consisting of alphanumeric characters and `_`
+    ///
+    /// * `what` - what the name means, used in error message
+    fn identifier(&mut self, what: &str) -> Result<&'a str, TemplateError> {
+        let rest = self.rest();
+        let length = rest
+            .find(|char: char| !(char.is_alphanumeric() || char == '_'))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(self.error(format!("Expected {what}.")));
+        }
+
+        self.offset += length;
+        Ok(&rest[..length])
+    }
+
+    /// Consume argument of a filter: number or text in double quotes
+    fn argument(&mut self) -> Result<Argument, TemplateError> {
+        let rest = self.rest();
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let mut text = String::new();
+            let mut chars = quoted.char_indices();
+            while let Some((index, char)) = chars.next() {
+                match char {
+                    '"' => {
+                        self.offset += index + 2;
+                        return Ok(Argument::Text(text));
+                    }
+                    '\\' => match chars.next() {
+                        Some((_, 'n')) => text.push('\n'),
+                        Some((_, escaped)) => text.push(escaped),
+                        None => break,
+                    },
+                    char => text.push(char),
+                }
+            }
+            return Err(self.error("Text argument is not closed with `\"`."));
+        }
+
+        let length = rest
+            .find(|char: char| !char.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let number = rest[..length].parse().map_err(|_| {
+            self.error("Expected number or text in double quotes.")
+        })?;
+        self.offset += length;
+
+        Ok(Argument::Number(number))
+    }
+
+    /// Consume filter with its argument
+    fn filter(&mut self) -> Result<Filter, TemplateError> {
+        let start = self.offset;
+        let name = self.identifier("filter name")?;
+        self.skip_whitespace();
+
+        let argument = if self.eat("(") {
+            self.skip_whitespace();
+            let argument = self.argument()?;
+            self.skip_whitespace();
+            self.expect(")")?;
+            Some(argument)
+        } else {
+            None
+        };
+
+        let error = |message: String| {
+            Err(TemplateError::new(self.source, start, message))
+        };
+        match (name, argument) {
+            ("upper", None) => Ok(Filter::Upper),
+            ("lower", None) => Ok(Filter::Lower),
+            ("trim", None) => Ok(Filter::Trim),
+            ("slug", None) => Ok(Filter::Slug),
+            ("truncate", Some(Argument::Number(length))) => {
+                Ok(Filter::Truncate(length))
+            }
+            ("default", Some(Argument::Text(default))) => {
+                Ok(Filter::Default(default))
+            }
+            ("truncate", _) => {
+                error(String::from("Filter `truncate` expects a number."))
+            }
+            ("default", _) => error(String::from(
+                "Filter `default` expects text in double quotes.",
+            )),
+            ("upper" | "lower" | "trim" | "slug", Some(_)) => {
+                error(format!("Filter `{name}` doesn't expect arguments."))
+            }
+            _ => error(format!("Unknown filter `{name}`.")),
+        }
+    }
+
+    /// Consume placeholder after its opening `{`
+    ///
+    /// * `offset` - byte offset of the opening `{`
+    fn placeholder(&mut self, offset: usize) -> Result<Node, TemplateError> {
+        self.skip_whitespace();
+        let name = self.identifier("placeholder name")?.to_string();
+        self.skip_whitespace();
+
+        let mut filters = Vec::new();
+        while self.eat("|") {
+            self.skip_whitespace();
+            filters.push(self.filter()?);
+            self.skip_whitespace();
+        }
+        self.expect("}")?;
+
+        Ok(Node::Placeholder {
+            name,
+            filters,
+            offset,
+        })
+    }
+
+    /// Consume nodes until the end of template or until block end tag
+    ///
+    /// * `opening` - byte offset of `{% if %}` tag if nodes are inside it
+    fn nodes(
+        &mut self,
+        opening: Option<usize>,
+    ) -> Result<(Vec<Node>, BlockEnd), TemplateError> {
+        let mut nodes = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            let start = self.offset;
+            let rest = self.rest();
+
+            if rest.is_empty() {
+                if let Some(opening) = opening {
+                    return Err(TemplateError::new(
+                        self.source,
+                        opening,
+                        "`{% if %}` is not closed with `{% endif %}`.",
+                    ));
+                }
+                break;
+            } else if self.eat("{{") {
+                text.push('{');
+            } else if self.eat("}}") {
+                text.push('}');
+            } else if self.eat("{%") {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                self.skip_whitespace();
+                let tag = self.identifier("`if`, `else` or `endif`")?;
+                self.skip_whitespace();
+
+                match tag {
+                    "if" => {
+                        let mut name = self.identifier("placeholder name")?;
+                        let negated = name == "not";
+                        if negated {
+                            self.skip_whitespace();
+                            name = self.identifier("placeholder name")?;
+                        }
+                        self.skip_whitespace();
+                        self.expect("%}")?;
+
+                        let (then, end) = self.nodes(Some(start))?;
+                        let otherwise = if end == BlockEnd::Else {
+                            match self.nodes(Some(start))? {
+                                (otherwise, BlockEnd::EndIf) => otherwise,
+                                (_, BlockEnd::Else) => {
+                                    return Err(TemplateError::new(
+                                        self.source,
+                                        start,
+                                        "`{% if %}` has several `{% else %}`.",
+                                    ))
+                                }
+                            }
+                        } else {
+                            Vec::new()
+                        };
+
+                        nodes.push(Node::If {
+                            name: name.to_string(),
+                            negated,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    "else" | "endif" => {
+                        self.expect("%}")?;
+                        if opening.is_none() {
+                            return Err(TemplateError::new(
+                                self.source,
+                                start,
+                                format!("Unexpected `{{% {tag} %}}`."),
+                            ));
+                        }
+                        let end = match tag {
+                            "else" => BlockEnd::Else,
+                            _ => BlockEnd::EndIf,
+                        };
+                        return Ok((nodes, end));
+                    }
+                    _ => {
+                        return Err(TemplateError::new(
+                            self.source,
+                            start,
+                            format!("Unknown tag `{tag}`."),
+                        ))
+                    }
+                }
+            } else if self.eat("{") {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                nodes.push(self.placeholder(start)?);
+            } else if rest.starts_with('}') {
+                return Err(self
+                    .error("Unmatched `}`, use `}}` to insert it as text."));
+            } else {
+                let length = rest.find(['{', '}']).unwrap_or(rest.len());
+                text.push_str(&rest[..length]);
+                self.offset += length;
+            }
+        }
+
+        if !text.is_empty() {
+            nodes.push(Node::Text(text));
+        }
+        Ok((nodes, BlockEnd::EndIf))
+    }
+}
+
+impl Template {
+    /// Parse template
+    ///
+    /// * `source` - text of the template
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut parser = Parser { source, offset: 0 };
+        let (nodes, _) = parser.nodes(None)?;
+
+        Ok(Template {
+            source: source.to_string(),
+            nodes,
+        })
+    }
+
+    /// Check if template has `{% if %}` blocks
+    pub fn has_conditions(&self) -> bool {
+        self.nodes
+            .iter()
+            .any(|node| matches!(node, Node::If { .. }))
+    }
+
+    /// Render template with the given placeholder values
+    ///
+    /// Unknown placeholder is an error unless it has `default` filter.
+    /// Unknown placeholder in `{% if %}` condition is treated as empty.
+    ///
+    /// * `values` - values of placeholders
+    pub fn render(
+        &self,
+        values: &HashMap<String, &str>,
+    ) -> Result<String, TemplateError> {
+        let mut result = String::new();
+        self.render_nodes(&self.nodes, values, &mut result)?;
+
+        Ok(result)
+    }
+
+    fn render_nodes(
+        &self,
+        nodes: &[Node],
+        values: &HashMap<String, &str>,
+        result: &mut String,
+    ) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => result.push_str(text),
+                Node::Placeholder {
+                    name,
+                    filters,
+                    offset,
+                } => {
+                    let has_default = filters
+                        .iter()
+                        .any(|filter| matches!(filter, Filter::Default(_)));
+                    let value = match values.get(name) {
+                        Some(value) => value.to_string(),
+                        None if has_default => String::new(),
+                        None => {
+                            return Err(TemplateError::new(
+                                &self.source,
+                                *offset,
+                                format!("Unknown placeholder `{name}`, use `subject`, `body`, `task_id` or named group of task regex."),
+                            ))
+                        }
+                    };
+
+                    result.push_str(
+                        &filters
+                            .iter()
+                            .fold(value, |value, filter| filter.apply(value)),
+                    );
+                }
+                Node::If {
+                    name,
+                    negated,
+                    then,
+                    otherwise,
+                } => {
+                    let is_set = values
+                        .get(name)
+                        .is_some_and(|value| !value.trim().is_empty());
+                    let branch =
+                        if is_set != *negated { then } else { otherwise };
+                    self.render_nodes(branch, values, result)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(source: &str, values: &[(&str, &str)]) -> String {
+        let values = values
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect();
+
+        Template::parse(source).unwrap().render(&values).unwrap()
+    }
+
+    #[test]
+    fn test_render_placeholders_and_escapes() {
+        assert_eq!(
+            render(
+                "{subject} {{{task_id}}}",
+                &[("subject", "Fix"), ("task_id", "ABC-1")]
+            ),
+            "Fix {ABC-1}"
+        );
+    }
+
+    #[test]
+    fn test_render_condition() {
+        let template =
+            "{subject}\n\n{% if body %}{body}\n\n{% endif %}{task_id}";
+        let values = [("subject", "Fix"), ("task_id", "ABC-1")];
+
+        assert_eq!(
+            render(template, &[("body", "Details"), values[0], values[1]]),
+            "Fix\n\nDetails\n\nABC-1"
+        );
+        assert_eq!(
+            render(template, &[("body", ""), values[0], values[1]]),
+            "Fix\n\nABC-1"
+        );
+        assert_eq!(
+            render("{% if not body %}empty{% else %}{body}{% endif %}", &[]),
+            "empty"
+        );
+    }
+
+    #[test]
+    fn test_render_filters() {
+        let values = [("subject", "  Fix the Login page!  "), ("type", "")];
+
+        assert_eq!(
+            render("{subject | trim | upper}", &values),
+            "FIX THE LOGIN PAGE!"
+        );
+        assert_eq!(
+            render("{subject|trim|lower}", &values),
+            "fix the login page!"
+        );
+        assert_eq!(render("{subject|trim|truncate(7)}", &values), "Fix the");
+        assert_eq!(render("{subject|slug}", &values), "fix-the-login-page");
+        assert_eq!(render("{type|default(\"chore\")}", &values), "chore");
+        assert_eq!(render("{scope|default(\"core\")}", &values), "core");
+    }
+
+    #[test]
+    fn test_parse_errors_have_position() {
+        let error = |source| Template::parse(source).unwrap_err();
+
+        assert_eq!(
+            error("{subject}\n\n{body|shout}"),
+            TemplateError {
+                line: 3,
+                column: 7,
+                message: String::from("Unknown filter `shout`."),
+            }
+        );
+        assert_eq!(error("{% if body %}{body}").column, 1);
+        assert_eq!(error("text }").column, 6);
+        assert_eq!(error("{subject|truncate(\"x\")}").column, 10);
+        assert_eq!(error("{% endif %}").message, "Unexpected `{% endif %}`.");
+    }
+
+    #[test]
+    fn test_render_unknown_placeholder() {
+        let error = Template::parse("{subject} {scope}")
+            .unwrap()
+            .render(&HashMap::from([(String::from("subject"), "Fix")]))
+            .unwrap_err();
+
+        assert_eq!((error.line, error.column), (1, 11));
+    }
+}
@@ -0,0 +1,185 @@
+//! Trailers at the end of commit message
+//!
+//! Trailer block is found the same way `git interpret-trailers` does it: it
+//! is the last paragraph of the message except the subject, where either
+//! every line is a trailer or at least 25% of lines are trailers and one of
+//! them is generated by git.
+
+/// Prefixes of trailers generated by git itself
+const GIT_GENERATED_PREFIXES: [&str; 2] =
+    ["Signed-off-by: ", "(cherry picked from commit "];
+
+/// Trailers attributing the commit, new trailers are inserted before them
+const ATTRIBUTION_KEYS: [&str; 2] = ["Signed-off-by", "Co-authored-by"];
+
+/// Return key of the trailer if the line is a trailer
+///
+/// * `line` - line of the message
+fn trailer_key(line: &str) -> Option<&str> {
+    if line.starts_with(GIT_GENERATED_PREFIXES[1]) {
+        return Some(line);
+    }
+
+    let (key, _) = line.split_once(':')?;
+    let key = key.trim_end();
+    let is_token = !key.is_empty()
+        && key
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '-');
+
+    is_token.then_some(key)
+}
+
+/// Return whether lines of the paragraph form trailer block
+///
+/// * `lines` - lines of the last paragraph
+fn is_trailer_block(lines: &[&str]) -> bool {
+    let mut trailers = 0;
+    let mut non_trailers = 0;
+    let mut has_git_generated = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 && line.starts_with([' ', '\t']) {
+            // Continuation of the previous line
+            continue;
+        }
+
+        if trailer_key(line).is_some() {
+            trailers += 1;
+            has_git_generated |= GIT_GENERATED_PREFIXES
+                .iter()
+                .any(|prefix| line.starts_with(prefix));
+        } else {
+            non_trailers += 1;
+        }
+    }
+
+    trailers > 0
+        && (non_trailers == 0
+            || (has_git_generated && trailers * 3 >= non_trailers))
+}
+
+/// Return message with the trailers added into trailer block
+///
+/// If the key already exists, new trailers are placed right after the last
+/// one with this key. Otherwise they are placed before `Signed-off-by` and
+/// `Co-authored-by` trailers, so their order is kept. New trailer block is
+/// created if message doesn't have one.
+///
+/// * `message` - commit message without comments
+/// * `key` - key of the trailers, e.g. `Refs`
+/// * `values` - value of every new trailer
+pub(crate) fn add_trailers(
+    message: &str,
+    key: &str,
+    values: &[String],
+) -> String {
+    let message = message.trim_end();
+    let new_lines: Vec<String> = values
+        .iter()
+        .map(|value| format!("{key}: {value}"))
+        .collect();
+
+    let block_start =
+        message
+            .rfind("\n\n")
+            .map(|index| index + 2)
+            .filter(|start| {
+                let lines: Vec<&str> = message[*start..].lines().collect();
+                is_trailer_block(&lines)
+            });
+    let Some(block_start) = block_start else {
+        return format!("{message}\n\n{}", new_lines.join("\n"));
+    };
+
+    let mut lines: Vec<&str> = message[block_start..].lines().collect();
+    let position_of = |keys: &[&str], last: bool| {
+        let mut found = lines.iter().enumerate().filter(|(_, line)| {
+            trailer_key(line).is_some_and(|line_key| {
+                keys.iter().any(|key| key.eq_ignore_ascii_case(line_key))
+            })
+        });
+        if last {
+            found.next_back()
+        } else {
+            found.next()
+        }
+        .map(|(index, _)| index)
+    };
+
+    let insert_at = match position_of(&[key], true) {
+        Some(index) => {
+            // Skip continuation lines of the existing trailer
+            index
+                + 1
+                + lines[index + 1..]
+                    .iter()
+                    .take_while(|line| line.starts_with([' ', '\t']))
+                    .count()
+        }
+        None => position_of(&ATTRIBUTION_KEYS, false).unwrap_or(lines.len()),
+    };
+    lines.splice(insert_at..insert_at, new_lines.iter().map(String::as_str));
+
+    format!("{}{}", &message[..block_start], lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn refs(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_add_trailers_creates_block() {
+        assert_eq!(
+            add_trailers("Subject", "Refs", &refs(&["ABC-1"])),
+            "Subject\n\nRefs: ABC-1"
+        );
+        assert_eq!(
+            add_trailers(
+                "Subject\n\nBody: not a trailer\nat all\n",
+                "Refs",
+                &refs(&["ABC-1"])
+            ),
+            "Subject\n\nBody: not a trailer\nat all\n\nRefs: ABC-1"
+        );
+        // Subject is never a trailer block
+        assert_eq!(
+            add_trailers("fix: Subject", "Refs", &refs(&["ABC-1"])),
+            "fix: Subject\n\nRefs: ABC-1"
+        );
+    }
+
+    #[test]
+    fn test_add_trailers_keeps_attribution_last() {
+        let message = "Subject\n\nBody\n\nReviewed-by: A\nCo-authored-by: B <b@example.com>\nSigned-off-by: C <c@example.com>";
+
+        assert_eq!(
+            add_trailers(message, "Refs", &refs(&["ABC-1", "ABC-2"])),
+            "Subject\n\nBody\n\nReviewed-by: A\nRefs: ABC-1\nRefs: ABC-2\nCo-authored-by: B <b@example.com>\nSigned-off-by: C <c@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_add_trailers_merges_with_existing_key() {
+        let message = "Subject\n\nrefs: ABC-1\n  continued\nSigned-off-by: C <c@example.com>\nAcked-by: D";
+
+        assert_eq!(
+            add_trailers(message, "Refs", &refs(&["ABC-2"])),
+            "Subject\n\nrefs: ABC-1\n  continued\nRefs: ABC-2\nSigned-off-by: C <c@example.com>\nAcked-by: D"
+        );
+    }
+
+    #[test]
+    fn test_add_trailers_to_mixed_block_with_git_generated_trailer() {
+        let message = "Subject\n\nSome note\nSigned-off-by: C <c@example.com>";
+
+        assert_eq!(
+            add_trailers(message, "Refs", &refs(&["ABC-1"])),
+            "Subject\n\nSome note\nRefs: ABC-1\nSigned-off-by: C <c@example.com>"
+        );
+    }
+}
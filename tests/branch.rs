@@ -0,0 +1,44 @@
+//! Branch name resolved with detached HEAD
+
+mod common;
+
+use common::{git, isolate};
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Run `extract` command in the directory
+fn extract(directory: &Path, args: &[&str]) -> Output {
+    isolate(&mut Command::new(env!("CARGO_BIN_EXE_pyrust_task_id")))
+        .args([
+            "extract",
+            "--task-regex",
+            r"feature/(?P<task_template>ABC-\d+)",
+        ])
+        .args(args)
+        .current_dir(directory)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_name_rev_fallback_is_opt_in() {
+    let directory = TempDir::new().unwrap();
+    let path = directory.path();
+    git(path, &["init", "-q", "-b", "main"]);
+    git(
+        path,
+        &["commit", "-q", "--allow-empty", "-m", "Initial commit"],
+    );
+    git(path, &["checkout", "-q", "-b", "feature/ABC-1"]);
+    git(path, &["commit", "-q", "--allow-empty", "-m", "Fix thing"]);
+    git(path, &["checkout", "-q", "--detach", "HEAD"]);
+
+    // Branch containing the commit may belong to another task
+    let output = extract(path, &[]);
+    assert!(!output.status.success(), "{output:?}");
+
+    let output = extract(path, &["--detached-name-rev"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "ABC-1\n");
+}
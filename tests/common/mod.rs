@@ -0,0 +1,25 @@
+//! Helpers running git and the binary in temporary repositories
+
+use std::path::Path;
+use std::process::Command;
+
+/// Set identity and isolate git from user and system config
+pub fn isolate(command: &mut Command) -> &mut Command {
+    command
+        .env("GIT_AUTHOR_NAME", "Author")
+        .env("GIT_AUTHOR_EMAIL", "author@example.com")
+        .env("GIT_COMMITTER_NAME", "Committer")
+        .env("GIT_COMMITTER_EMAIL", "committer@example.com")
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+}
+
+/// Run git command in the directory and return its stdout
+pub fn git(directory: &Path, args: &[&str]) -> String {
+    let output = isolate(Command::new("git").args(args))
+        .current_dir(directory)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {args:?} failed: {output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
@@ -0,0 +1,125 @@
+//! `rewrite` command run against a real repository
+
+mod common;
+
+use common::{git, isolate};
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Run `rewrite` command in the directory
+fn rewrite(directory: &Path, args: &[&str]) -> Output {
+    isolate(&mut Command::new(env!("CARGO_BIN_EXE_pyrust_task_id")))
+        .args([
+            "rewrite",
+            "--task-regex",
+            r"feature/(?P<task_template>ABC-\d+)",
+        ])
+        .args(["--template", r"{subject}\n\n{body}\n\n{task_id}"])
+        .args(args)
+        .current_dir(directory)
+        .output()
+        .unwrap()
+}
+
+/// Create repository on `feature/ABC-1` branch with pushed `main`
+fn init_repository() -> (TempDir, TempDir) {
+    let remote = TempDir::new().unwrap();
+    git(remote.path(), &["init", "-q", "--bare"]);
+
+    let directory = TempDir::new().unwrap();
+    let path = directory.path();
+    git(path, &["init", "-q", "-b", "main"]);
+    git(
+        path,
+        &["commit", "-q", "--allow-empty", "-m", "Initial commit"],
+    );
+    git(
+        path,
+        &["remote", "add", "origin", remote.path().to_str().unwrap()],
+    );
+    git(path, &["push", "-q", "origin", "main"]);
+    git(path, &["checkout", "-q", "-b", "feature/ABC-1"]);
+
+    (directory, remote)
+}
+
+#[test]
+fn test_rewrite_inserts_task_id_keeping_comment_like_lines() {
+    let (directory, _remote) = init_repository();
+    let path = directory.path();
+    std::fs::write(path.join("file"), "content").unwrap();
+    git(path, &["add", "file"]);
+    git(
+        path,
+        &[
+            "commit",
+            "-q",
+            "-m",
+            "Fix thing\n\n#42 was the issue\nmore text",
+        ],
+    );
+    git(
+        path,
+        &["commit", "-q", "--allow-empty", "-m", "Add ABC-1 test"],
+    );
+    let old_head = git(path, &["rev-parse", "HEAD"]);
+    let author_date = git(path, &["log", "-1", "--format=%ad", "HEAD~1"]);
+
+    let output = rewrite(path, &["--dry-run", "origin/main..HEAD"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(git(path, &["rev-parse", "HEAD"]), old_head);
+
+    let output = rewrite(path, &["origin/main..HEAD"]);
+    assert!(output.status.success(), "{output:?}");
+    // Both commits are copied, the second one only gets the new parent
+    let mapping = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(mapping.lines().count(), 2);
+    assert!(mapping.ends_with(&format!(
+        "{}\n",
+        git(path, &["rev-parse", "HEAD"]).trim()
+    )));
+
+    assert_eq!(
+        git(path, &["log", "-1", "--format=%B", "HEAD~1"]),
+        "Fix thing\n\n#42 was the issue\nmore text\n\nABC-1\n\n"
+    );
+    assert_eq!(
+        git(path, &["log", "-1", "--format=%B", "HEAD"]),
+        "Add ABC-1 test\n\n"
+    );
+    assert_eq!(
+        git(path, &["log", "-1", "--format=%ad", "HEAD~1"]),
+        author_date
+    );
+    assert_eq!(git(path, &["status", "--porcelain"]), "");
+}
+
+#[test]
+fn test_rewrite_refuses_pushed_and_merge_commits() {
+    let (directory, _remote) = init_repository();
+    let path = directory.path();
+    git(path, &["commit", "-q", "--allow-empty", "-m", "Fix thing"]);
+    git(path, &["push", "-q", "origin", "feature/ABC-1"]);
+    let pushed_head = git(path, &["rev-parse", "HEAD"]);
+
+    let output = rewrite(path, &["origin/main..HEAD"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already pushed"));
+    assert_eq!(git(path, &["rev-parse", "HEAD"]), pushed_head);
+
+    let output = rewrite(path, &["--force", "origin/main..HEAD"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_ne!(git(path, &["rev-parse", "HEAD"]), pushed_head);
+
+    git(path, &["checkout", "-q", "-b", "side", "main"]);
+    git(path, &["commit", "-q", "--allow-empty", "-m", "Side"]);
+    git(path, &["checkout", "-q", "feature/ABC-1"]);
+    git(path, &["merge", "-q", "--no-ff", "--no-edit", "side"]);
+    let merge_head = git(path, &["rev-parse", "HEAD"]);
+
+    let output = rewrite(path, &["HEAD~1..HEAD"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("merge commit"));
+    assert_eq!(git(path, &["rev-parse", "HEAD"]), merge_head);
+}